@@ -0,0 +1,74 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use enigma::convert::char_to_wire;
+use enigma::{wirings, Machine, ALPHABET_SIZE};
+
+fn machine() -> Machine {
+    Machine::new(
+        ("IV", "II", "V"),
+        (1, 20, 11),
+        (0, 3, 20),
+        "B",
+        "AV BS CG DL FU HZ IN KM OW RX",
+    )
+}
+
+fn encrypt_char(c: &mut Criterion) {
+    let mut machine = machine();
+    c.bench_function("encrypt_char", |b| {
+        b.iter(|| machine.encrypt_char(black_box('A')))
+    });
+}
+
+fn encrypt_message(c: &mut Criterion) {
+    let message = "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG".repeat(300);
+    let mut group = c.benchmark_group("encrypt_message");
+    group.throughput(Throughput::Bytes(message.len() as u64));
+
+    // Letter by letter through `str`.
+    group.bench_function("encrypt", |b| {
+        let mut machine = machine();
+        b.iter(|| machine.encrypt(black_box(&message)))
+    });
+    // The table-driven path used for bytes and readers.
+    group.bench_function("encrypt_bytes", |b| {
+        let mut machine = machine();
+        b.iter(|| machine.encrypt_bytes(black_box(message.as_bytes())))
+    });
+    group.finish();
+}
+
+/// Inverts `wiring` by indexing it, as rotors used to.
+fn invert_indexed(wiring: &[u8; ALPHABET_SIZE]) -> [u8; ALPHABET_SIZE] {
+    let mut inverse = [0; ALPHABET_SIZE];
+    for i in 0..inverse.len() {
+        inverse[wiring[i] as usize] = i as u8;
+    }
+    inverse
+}
+
+/// Inverts `wiring` by enumerating it, as the crate's `invert_wiring` does.
+fn invert_enumerated(wiring: &[u8; ALPHABET_SIZE]) -> [u8; ALPHABET_SIZE] {
+    let mut inverse = [0; ALPHABET_SIZE];
+    for (i, &w) in wiring.iter().enumerate() {
+        inverse[w as usize] = i as u8;
+    }
+    inverse
+}
+
+fn invert_wiring(c: &mut Criterion) {
+    let mut wiring = [0; ALPHABET_SIZE];
+    for (w, l) in wiring.iter_mut().zip(wirings::ROTOR_I.chars()) {
+        *w = char_to_wire(l);
+    }
+    assert_eq!(invert_indexed(&wiring), invert_enumerated(&wiring));
+
+    let mut group = c.benchmark_group("invert_wiring");
+    group.bench_function("indexed", |b| b.iter(|| invert_indexed(black_box(&wiring))));
+    group.bench_function("enumerated", |b| {
+        b.iter(|| invert_enumerated(black_box(&wiring)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, encrypt_char, encrypt_message, invert_wiring);
+criterion_main!(benches);
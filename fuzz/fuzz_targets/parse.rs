@@ -0,0 +1,31 @@
+#![no_main]
+
+use enigma::text::{self, DigitPolicy, GERMAN_DIGITS};
+use enigma::{Machine, MachineConfig, Operator, Plugboard, Reflector, Rotor};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    if let Ok(config) = data.parse::<MachineConfig>() {
+        if let Ok(mut machine) = config.build() {
+            machine.encrypt(data);
+        }
+    }
+    let _ = data.parse::<Machine>();
+    if let Ok(config) = MachineConfig::from_keysheet_line(data) {
+        let _ = config.build();
+    }
+
+    let _ = Plugboard::new(data);
+    let _ = Plugboard::from_connections(data);
+    let _ = Rotor::try_new(data, 0, 0, vec![]);
+    let _ = Rotor::try_from_name(data, 0, 0);
+    let _ = Reflector::try_from_encoding(data);
+    let _ = Reflector::try_from_name(data);
+
+    if let Ok(mut operator) = Operator::new(Machine::default_m3(), "AAA") {
+        let _ = operator.receive(data);
+    }
+
+    let _ = text::from_morse(data);
+    let _ = text::restore_digits(data, DigitPolicy::Replace(&GERMAN_DIGITS));
+});
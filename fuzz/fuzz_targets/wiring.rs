@@ -0,0 +1,20 @@
+#![no_main]
+
+use enigma::{Reflector, Rotor, ALPHABET_SIZE};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Rotor::from_bytes(data, 0, 0, vec![]);
+    let _ = Reflector::from_bytes(data);
+
+    if let Ok(wiring) = <[u8; ALPHABET_SIZE]>::try_from(data) {
+        if let Ok(rotor) = Rotor::from_array(wiring, 0, 0, vec![]) {
+            assert_eq!(rotor.forward_table(), wiring);
+        }
+        if let Ok(reflector) = Reflector::from_array(wiring) {
+            for c in 'A'..='Z' {
+                assert_eq!(reflector.reflect_char(reflector.reflect_char(c)), c);
+            }
+        }
+    }
+});
@@ -0,0 +1,38 @@
+/* C interface to the enigma crate, built with the `ffi` feature. See src/ffi.rs. */
+#ifndef ENIGMA_H
+#define ENIGMA_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Opaque handle to a machine. */
+typedef struct EnigmaMachine EnigmaMachine;
+
+/* Creates a machine from 3 rotor names, 3 ring settings, 3 positions, a reflector name and
+ * plugboard connections, e.g. "AB CD". Returns NULL on error, see enigma_last_error. */
+EnigmaMachine *enigma_new(const char *const *rotors, const size_t *ring_settings,
+                          const size_t *positions, const char *reflector,
+                          const char *plugboard);
+
+/* Encrypts a message, stepping the machine. The result must be released with
+ * enigma_string_free. Returns NULL on error, see enigma_last_error. */
+char *enigma_encrypt(EnigmaMachine *machine, const char *message);
+
+/* Releases a machine. Does nothing if machine is NULL. */
+void enigma_free(EnigmaMachine *machine);
+
+/* Releases a string returned by enigma_encrypt. Does nothing if s is NULL. */
+void enigma_string_free(char *s);
+
+/* Describes the last error on the calling thread, or NULL if there hasn't been one. Owned by
+ * the library and valid until the next failing call on the same thread. */
+const char *enigma_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,199 @@
+//! Statistics for analysing plaintexts and ciphertexts.
+use crate::{char_to_wire, ALPHABET_SIZE};
+
+/// Relative frequencies of the letters A-Z in `text`, ignoring anything that isn't an ASCII letter.
+///
+/// The frequencies sum to 1, unless `text` contains no letters in which case they're all 0.
+///
+/// # Examples
+///
+/// ```
+/// use enigma::analysis::letter_frequencies;
+/// let frequencies = letter_frequencies("Abba!");
+/// assert_eq!(frequencies[0], 0.5);
+/// assert_eq!(frequencies[1], 0.5);
+/// assert_eq!(frequencies[2], 0.0);
+/// ```
+pub fn letter_frequencies(text: &str) -> [f64; ALPHABET_SIZE] {
+    let mut counts = [0usize; ALPHABET_SIZE];
+    for c in text.chars().filter(|c| c.is_ascii_alphabetic()) {
+        counts[char_to_wire(c) as usize] += 1;
+    }
+
+    let total: usize = counts.iter().sum();
+    let mut frequencies = [0.0; ALPHABET_SIZE];
+    if total > 0 {
+        for (f, &count) in frequencies.iter_mut().zip(counts.iter()) {
+            *f = count as f64 / total as f64;
+        }
+    }
+
+    frequencies
+}
+
+/// Relative frequencies of the letters A-Z in typical English text.
+pub const ENGLISH_FREQUENCIES: [f64; ALPHABET_SIZE] = [
+    0.08167, 0.01492, 0.02782, 0.04253, 0.12702, 0.02228, 0.02015, 0.06094, 0.06966, 0.00153,
+    0.00772, 0.04025, 0.02406, 0.06749, 0.07507, 0.01929, 0.00095, 0.05987, 0.06327, 0.09056,
+    0.02758, 0.00978, 0.02360, 0.00150, 0.01974, 0.00074,
+];
+
+/// Chi-squared statistic of the letter counts in `text` against [`ENGLISH_FREQUENCIES`], ignoring
+/// anything that isn't an ASCII letter.
+///
+/// Lower values are more English-like. This is cheap to compute, so makes a good first pass when
+/// searching for settings. Text without any letters can't be scored and gives infinity.
+///
+/// # Examples
+///
+/// ```
+/// use enigma::analysis::chi_squared_english;
+/// assert!(chi_squared_english("THEQUICKBROWNFOX") < chi_squared_english("QXZJQXZJKVQXZJKV"));
+/// ```
+pub fn chi_squared_english(text: &str) -> f64 {
+    let total = text.chars().filter(|c| c.is_ascii_alphabetic()).count();
+    if total == 0 {
+        return f64::INFINITY;
+    }
+
+    letter_frequencies(text)
+        .iter()
+        .zip(ENGLISH_FREQUENCIES.iter())
+        .map(|(&observed, &expected)| {
+            let observed = observed * total as f64;
+            let expected = expected * total as f64;
+            (observed - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+/// Indices of the characters where `expected` and `actual` differ, e.g. to find where a
+/// configuration that's almost right goes wrong. Where one string is longer, its extra characters
+/// all count as differences.
+///
+/// # Examples
+///
+/// ```
+/// use enigma::analysis::diff;
+/// assert_eq!(diff("HELLOXWORLD", "HELLOXWORLD"), Vec::<usize>::new());
+/// assert_eq!(diff("HELLOXWORLD", "HELPOXWORKD"), vec![3, 9]);
+/// ```
+pub fn diff(expected: &str, actual: &str) -> Vec<usize> {
+    let expected: Vec<char> = expected.chars().collect();
+    let actual: Vec<char> = actual.chars().collect();
+    (0..expected.len().max(actual.len()))
+        .filter(|&i| expected.get(i) != actual.get(i))
+        .collect()
+}
+
+/// Shows `expected` above `actual` with the characters that differ, as found by [`diff`],
+/// underlined by `^`. Gives an empty string if they're the same.
+///
+/// # Examples
+///
+/// ```
+/// use enigma::analysis::format_diff;
+/// assert_eq!(
+///     format_diff("HELLOXWORLD", "HELPOXWORKD"),
+///     "expected: HELLOXWORLD\nactual:   HELPOXWORKD\n             ^     ^\n"
+/// );
+/// ```
+pub fn format_diff(expected: &str, actual: &str) -> String {
+    let differences = diff(expected, actual);
+    let Some(&last) = differences.last() else {
+        return String::new();
+    };
+
+    let mut markers = vec![' '; last + 1];
+    for i in differences {
+        markers[i] = '^';
+    }
+    format!(
+        "expected: {}\nactual:   {}\n          {}\n",
+        expected,
+        actual,
+        markers.into_iter().collect::<String>()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Plugboard, Reflector, Rotor};
+    use crate::Machine;
+
+    const PLAINTEXT: &str = "TOMORROWANDTOMORROWANDTOMORROWCREEPSINTHISPETTYPACEFROMDAYTODAYTOTHELASTSYLLABLEOFRECORDEDTIMEANDALLOURYESTERDAYSHAVELIGHTEDFOOLSTHEWAYTODUSTYDEATHOUTOUTBRIEFCANDLELIFESBUTAWALKINGSHADOWAPOORPLAYERTHATSTRUTSANDFRETSHISHOURUPONTHESTAGEANDTHENISHEARDNOMOREITISATALETOLDBYANIDIOTFULLOFSOUNDANDFURYSIGNIFYINGNOTHING";
+
+    fn sum_of_squares(frequencies: [f64; ALPHABET_SIZE]) -> f64 {
+        frequencies.iter().map(|f| f * f).sum()
+    }
+
+    #[test]
+    fn test_letter_frequencies() {
+        assert_eq!(letter_frequencies(""), [0.0; ALPHABET_SIZE]);
+        assert_eq!(letter_frequencies("123 !"), [0.0; ALPHABET_SIZE]);
+
+        let frequencies = letter_frequencies("aAbz, Z");
+        assert_eq!(frequencies[0], 0.4);
+        assert_eq!(frequencies[1], 0.2);
+        assert_eq!(frequencies[25], 0.4);
+        assert!((frequencies.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_chi_squared_english() {
+        assert_eq!(chi_squared_english(""), f64::INFINITY);
+        assert_eq!(chi_squared_english("1234"), f64::INFINITY);
+        assert!((ENGLISH_FREQUENCIES.iter().sum::<f64>() - 1.0).abs() < 1e-3);
+
+        let mut machine = Machine::new(("I", "II", "III"), (0, 0, 0), (0, 0, 0), "B", "");
+        let encrypted = machine.encrypt(PLAINTEXT);
+        assert!(chi_squared_english(PLAINTEXT) < 100.0);
+        assert!(chi_squared_english(&encrypted) > 3.0 * chi_squared_english(PLAINTEXT));
+
+        assert_eq!(
+            chi_squared_english(PLAINTEXT),
+            chi_squared_english(&PLAINTEXT.to_lowercase())
+        );
+    }
+
+    #[test]
+    fn test_output_flatness() {
+        // With an identity reflector each letter comes back out the way it went in, so the output
+        // keeps the skewed frequencies of English.
+        let mut degenerate = Machine::from_components(
+            (Rotor::i(0, 0), Rotor::ii(0, 0), Rotor::iii(0, 0)),
+            Reflector::default(),
+            Plugboard::identity(),
+        );
+        let degenerate = degenerate.encrypt(PLAINTEXT);
+        assert_eq!(degenerate, PLAINTEXT);
+
+        let mut machine = Machine::new(("I", "II", "III"), (0, 0, 0), (0, 0, 0), "B", "");
+        let encrypted = machine.encrypt(PLAINTEXT);
+
+        let uniform = 1.0 / ALPHABET_SIZE as f64;
+        let degenerate = sum_of_squares(letter_frequencies(&degenerate));
+        let encrypted = sum_of_squares(letter_frequencies(&encrypted));
+        assert!(degenerate > 0.06);
+        assert!(encrypted < 0.045);
+        assert!(encrypted - uniform < (degenerate - uniform) / 3.0);
+    }
+
+    #[test]
+    fn test_diff() {
+        assert!(diff("", "").is_empty());
+        assert_eq!(diff("ABC", "ABD"), vec![2]);
+        assert_eq!(diff("ABC", "ABCDE"), vec![3, 4]);
+        assert_eq!(diff("ABCDE", "XBC"), vec![0, 3, 4]);
+    }
+
+    #[test]
+    fn test_format_diff() {
+        assert_eq!(format_diff("ABC", "ABC"), "");
+        assert_eq!(
+            format_diff("ABC", "XB"),
+            "expected: ABC\nactual:   XB\n          ^ ^\n"
+        );
+    }
+}
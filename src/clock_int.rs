@@ -0,0 +1,150 @@
+use crate::{try_char_to_wire, wire_to_char, InvalidArgsError, ALPHABET_SIZE};
+use std::fmt::Display;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+
+/// An integer modulo [`ALPHABET_SIZE`], like the letters around the rim of a rotor.
+///
+/// Used for rotor positions and ring settings. Values are reduced as they're created and all
+/// arithmetic wraps around, so `Z + 1 == A`.
+///
+/// # Examples
+///
+/// ```
+/// use enigma::ClockInt;
+/// let z = ClockInt::try_from('Z').unwrap();
+/// assert_eq!(z + ClockInt::new(1), ClockInt::try_from('A').unwrap());
+/// assert_eq!(ClockInt::new(27).to_char(), 'B');
+/// assert_eq!(ClockInt::try_from('C').unwrap().to_string(), "C");
+/// assert!(ClockInt::try_from('[').is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ClockInt(u8);
+
+impl ClockInt {
+    /// Creates a new [`ClockInt`], reducing `value` modulo [`ALPHABET_SIZE`].
+    pub fn new(value: usize) -> Self {
+        Self((value % ALPHABET_SIZE) as u8)
+    }
+
+    /// The value as a number in `0..ALPHABET_SIZE`.
+    pub fn value(self) -> usize {
+        self.0 as usize
+    }
+
+    /// The value as an uppercase letter, where 0 is `A`.
+    pub fn to_char(self) -> char {
+        wire_to_char(self.0)
+    }
+}
+
+impl From<usize> for ClockInt {
+    fn from(value: usize) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<u8> for ClockInt {
+    fn from(value: u8) -> Self {
+        Self::new(value as usize)
+    }
+}
+
+/// Converts a letter, in either case, to its number where `A` is 0.
+///
+/// Fails with [`InvalidArgsError::NotALetter`] if `c` isn't an ASCII letter.
+impl TryFrom<char> for ClockInt {
+    type Error = InvalidArgsError;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        try_char_to_wire(c).map(Self)
+    }
+}
+
+impl From<ClockInt> for usize {
+    fn from(value: ClockInt) -> Self {
+        value.value()
+    }
+}
+
+impl From<ClockInt> for u8 {
+    fn from(value: ClockInt) -> Self {
+        value.0
+    }
+}
+
+impl Add for ClockInt {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self((self.0 + rhs.0) % ALPHABET_SIZE as u8)
+    }
+}
+
+impl Sub for ClockInt {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self((self.0 + ALPHABET_SIZE as u8 - rhs.0) % ALPHABET_SIZE as u8)
+    }
+}
+
+impl AddAssign for ClockInt {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for ClockInt {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+/// Displays the value as a letter.
+impl Display for ClockInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(ClockInt::new(25) + ClockInt::new(3), ClockInt::new(2));
+        assert_eq!(ClockInt::new(2) - ClockInt::new(3), ClockInt::new(25));
+        assert_eq!(ClockInt::new(52).value(), 0);
+
+        let mut c = ClockInt::new(24);
+        c += ClockInt::new(2);
+        assert_eq!(c.value(), 0);
+        c -= ClockInt::new(1);
+        assert_eq!(c.value(), 25);
+    }
+
+    #[test]
+    fn test_char_conversions() {
+        assert_eq!(ClockInt::try_from('A').unwrap().value(), 0);
+        assert_eq!(ClockInt::try_from('z').unwrap().value(), 25);
+        assert_eq!(ClockInt::new(16).to_char(), 'Q');
+        for c in 'A'..='Z' {
+            assert_eq!(ClockInt::try_from(c).unwrap().to_char(), c);
+            assert_eq!(
+                ClockInt::try_from(c.to_ascii_lowercase())
+                    .unwrap()
+                    .to_char(),
+                c
+            );
+        }
+        assert_eq!(format!("{}", ClockInt::new(25)), "Z");
+    }
+
+    #[test]
+    fn test_from_non_letter() {
+        for c in ['[', '@', '1', ' ', 'Ä'] {
+            assert_eq!(ClockInt::try_from(c), Err(InvalidArgsError::NotALetter(c)));
+        }
+    }
+}
@@ -1,89 +1,601 @@
-use crate::{char_to_wire, encoding_to_wiring, identity_wiring, ALPHABET_SIZE};
+use crate::{
+    char_to_wire, check_wiring, identity_wiring, invert_wiring, try_char_to_wire,
+    try_encoding_to_wiring, wire_to_char, wiring_from_bytes, wiring_to_encoding, wirings, ClockInt,
+    InvalidArgsError, ALPHABET_SIZE,
+};
 use std::collections::hash_set::HashSet;
 
-pub(crate) struct Rotor {
+/// A part of the machine that substitutes one wire for another without changing any state.
+///
+/// Wires are the letters A-Z numbered from 0.
+pub trait Component {
+    /// Substitutes `letter` on its way towards the reflector.
+    fn forward(&self, letter: u8) -> u8;
+
+    /// Substitutes `letter` on its way back from the reflector.
+    fn backward(&self, letter: u8) -> u8;
+}
+
+/// A part of the machine that steps every time a letter passes through it.
+///
+/// Unlike [`Component`] this takes `&mut self`, as stepping changes the state used for the
+/// substitution. The step normally happens before the substitution, as on the real machine.
+pub trait SteppingComponent {
+    /// Steps, then substitutes `letter`.
+    fn encipher(&mut self, letter: u8) -> u8;
+}
+
+/// The wire `index` is connected to in `wiring`, used by the [`Component`] implementations.
+#[cfg(not(feature = "constant_time"))]
+fn lookup(wiring: &[u8; ALPHABET_SIZE], index: usize) -> u8 {
+    wiring[index]
+}
+
+/// The wire `index` is connected to in `wiring`, used by the [`Component`] implementations.
+///
+/// With the `constant_time` feature this reads every entry of `wiring` and picks the right one
+/// without branching, so which memory is read doesn't depend on the letter. It's several times
+/// slower and purely illustrative: the rest of the machine, such as stepping and the tables used
+/// by [`Machine::encrypt`](crate::Machine::encrypt), makes no such effort.
+#[cfg(feature = "constant_time")]
+fn lookup(wiring: &[u8; ALPHABET_SIZE], index: usize) -> u8 {
+    use subtle::{ConditionallySelectable, ConstantTimeEq};
+
+    let index = index as u8;
+    let mut out = 0;
+    for (i, &w) in wiring.iter().enumerate() {
+        out.conditional_assign(&w, (i as u8).ct_eq(&index));
+    }
+    out
+}
+
+/// The families of historical rotors. Rotors from different families weren't used together, except
+/// the [`Greek`](RotorFamily::Greek) rotors alongside the Wehrmacht ones in the M4.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RotorFamily {
+    /// Rotors I-VIII of the Wehrmacht and Kriegsmarine machines. This is the standard set.
+    Wehrmacht,
+    /// Rotors of the Norway Enigma, rewired for use in occupied Norway after the war.
+    Norway,
+    /// Rotors of the Abwehr's Enigma G-312, with many notches each.
+    G,
+    /// Rotors of the railway Enigma, an Enigma K rewired for the Reichsbahn.
+    Railway,
+    /// The thin Greek rotors of the Kriegsmarine M4, used in its fourth slot.
+    Greek,
+}
+
+/// Names of the historical rotors, grouped by [`RotorFamily`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum RotorName {
+    /// Wehrmacht rotor I.
+    I,
+    /// Wehrmacht rotor II.
+    II,
+    /// Wehrmacht rotor III.
+    III,
+    /// Wehrmacht rotor IV.
+    IV,
+    /// Wehrmacht rotor V.
+    V,
+    /// Kriegsmarine rotor VI.
+    VI,
+    /// Kriegsmarine rotor VII.
+    VII,
+    /// Kriegsmarine rotor VIII.
+    VIII,
+    /// Norway Enigma rotor I.
+    NorwayI,
+    /// Norway Enigma rotor II.
+    NorwayII,
+    /// Norway Enigma rotor III.
+    NorwayIII,
+    /// Norway Enigma rotor IV.
+    NorwayIV,
+    /// Norway Enigma rotor V.
+    NorwayV,
+    /// Enigma G-312 rotor I.
+    GI,
+    /// Enigma G-312 rotor II.
+    GII,
+    /// Enigma G-312 rotor III.
+    GIII,
+    /// Railway Enigma rotor I.
+    RailwayI,
+    /// Railway Enigma rotor II.
+    RailwayII,
+    /// Railway Enigma rotor III.
+    RailwayIII,
+    /// M4 Greek rotor beta.
+    Beta,
+    /// M4 Greek rotor gamma.
+    Gamma,
+}
+
+impl RotorName {
+    /// Every rotor name, with the standard Wehrmacht set first.
+    pub const ALL: [RotorName; 21] = [
+        Self::I,
+        Self::II,
+        Self::III,
+        Self::IV,
+        Self::V,
+        Self::VI,
+        Self::VII,
+        Self::VIII,
+        Self::NorwayI,
+        Self::NorwayII,
+        Self::NorwayIII,
+        Self::NorwayIV,
+        Self::NorwayV,
+        Self::GI,
+        Self::GII,
+        Self::GIII,
+        Self::RailwayI,
+        Self::RailwayII,
+        Self::RailwayIII,
+        Self::Beta,
+        Self::Gamma,
+    ];
+
+    /// The name as accepted by [`Rotor::from_name`], e.g. `"IV"` or `"NORWAY-II"`.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::I => "I",
+            Self::II => "II",
+            Self::III => "III",
+            Self::IV => "IV",
+            Self::V => "V",
+            Self::VI => "VI",
+            Self::VII => "VII",
+            Self::VIII => "VIII",
+            Self::NorwayI => "NORWAY-I",
+            Self::NorwayII => "NORWAY-II",
+            Self::NorwayIII => "NORWAY-III",
+            Self::NorwayIV => "NORWAY-IV",
+            Self::NorwayV => "NORWAY-V",
+            Self::GI => "G-I",
+            Self::GII => "G-II",
+            Self::GIII => "G-III",
+            Self::RailwayI => "RAILWAY-I",
+            Self::RailwayII => "RAILWAY-II",
+            Self::RailwayIII => "RAILWAY-III",
+            Self::Beta => "BETA",
+            Self::Gamma => "GAMMA",
+        }
+    }
+
+    /// The family the rotor belongs to.
+    pub fn family(self) -> RotorFamily {
+        match self {
+            Self::I
+            | Self::II
+            | Self::III
+            | Self::IV
+            | Self::V
+            | Self::VI
+            | Self::VII
+            | Self::VIII => RotorFamily::Wehrmacht,
+            Self::NorwayI | Self::NorwayII | Self::NorwayIII | Self::NorwayIV | Self::NorwayV => {
+                RotorFamily::Norway
+            }
+            Self::GI | Self::GII | Self::GIII => RotorFamily::G,
+            Self::RailwayI | Self::RailwayII | Self::RailwayIII => RotorFamily::Railway,
+            Self::Beta | Self::Gamma => RotorFamily::Greek,
+        }
+    }
+}
+
+impl TryFrom<&str> for RotorName {
+    type Error = InvalidArgsError;
+
+    fn try_from(name: &str) -> Result<Self, Self::Error> {
+        if name.trim().is_empty() {
+            return Err(InvalidArgsError::Empty("rotor"));
+        }
+        Self::ALL
+            .into_iter()
+            .find(|n| n.as_str() == name)
+            .ok_or_else(|| InvalidArgsError::InvalidRotorName(name.to_string()))
+    }
+}
+
+impl std::fmt::Display for RotorName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Passes a letter through `map` as a wire number, for the `*_char` methods of the components.
+pub(crate) fn map_char(c: char, map: impl Fn(u8) -> u8) -> char {
+    let w = try_char_to_wire(c).unwrap_or_else(|e| panic!("{}", e));
+    wire_to_char(map(w))
+}
+
+pub(crate) const ROTOR_NAMES: [&str; RotorName::ALL.len()] = {
+    let mut names = [""; RotorName::ALL.len()];
+    let mut i = 0;
+    while i < names.len() {
+        names[i] = RotorName::ALL[i].as_str();
+        i += 1;
+    }
+    names
+};
+
+pub(crate) const REFLECTOR_NAMES: [&str; 9] = [
+    "A", "B", "C", "D", "NORWAY", "G", "RAILWAY", "B-THIN", "C-THIN",
+];
+
+/// One of the machine's rotating wheels, wiring each contact on one side to a different contact on
+/// the other.
+///
+/// The alphabet ring can be rotated relative to the wiring by the ring setting, and the rotor
+/// causes the rotor to its left to step when it turns over from one of its notch positions.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Rotor {
     forward_wiring: [u8; ALPHABET_SIZE],
     backward_wiring: [u8; ALPHABET_SIZE],
-    position: usize,
-    ring_setting: usize,
-    notch_position: Vec<usize>,
+    position: ClockInt,
+    ring_setting: ClockInt,
+    notch_position: Vec<ClockInt>,
 }
 
 macro_rules! rotor_cons {
-    ($name:ident, $encoding:literal, $notches: expr) => {
+    ($name:ident, $wiring:ident, $notches:ident) => {
+        /// Creates the historical rotor of this name, with the wiring and notches from
+        /// [`wirings`].
         pub fn $name(ring_setting: usize, position: usize) -> Self {
-            Self::new($encoding, position, ring_setting, $notches)
+            let notches = wirings::$notches
+                .chars()
+                .map(|c| char_to_wire(c) as usize)
+                .collect();
+            Self::new(wirings::$wiring, ring_setting, position, notches)
         }
     };
 }
 
 impl Rotor {
+    /// Creates a new [`Rotor`] from its wiring, given as the letters the contacts A-Z are connected
+    /// to, and the positions at which it turns over the next rotor. A rotor may have several
+    /// notches, in any order.
+    ///
+    /// `ring_setting` counts from 0, so the ring setting listed as 01 on a key sheet is `0`.
+    /// `ring_setting`, `position` and the notch positions are reduced modulo [`ALPHABET_SIZE`] as
+    /// they're converted to [`ClockInt`]s, so the original values aren't kept. The ring setting
+    /// comes before the position, as in [`Rotor::from_name`] and
+    /// [`Machine::new`](crate::Machine::new).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `encoding` isn't a permutation of A-Z. See [`Rotor::try_new`].
     pub fn new(
         encoding: impl Into<String>,
-        position: usize,
         ring_setting: usize,
+        position: usize,
         notch_position: Vec<usize>,
     ) -> Self {
-        let forward_wiring = encoding_to_wiring(encoding);
+        Self::try_new(encoding, ring_setting, position, notch_position)
+            .expect("invalid rotor wiring")
+    }
 
-        let mut backward_wiring = [0; ALPHABET_SIZE];
-        for i in 0..backward_wiring.len() {
-            backward_wiring[forward_wiring[i] as usize] = i as u8;
-        }
+    /// Creates a new [`Rotor`] like [`Rotor::new`], returning an error if `encoding` isn't a
+    /// permutation of A-Z. The ring setting comes before the position, as in
+    /// [`Rotor::from_name`].
+    pub fn try_new(
+        encoding: impl Into<String>,
+        ring_setting: usize,
+        position: usize,
+        notch_position: Vec<usize>,
+    ) -> Result<Self, InvalidArgsError> {
+        let forward_wiring = try_encoding_to_wiring(encoding)?;
+        Ok(Self::from_checked_array(
+            forward_wiring,
+            ring_setting,
+            position,
+            notch_position,
+        ))
+    }
 
-        Self {
+    /// Creates a new [`Rotor`] like [`Rotor::try_new`] from a wiring that's already been
+    /// converted to wires, where `wiring[i]` is the wire contact `i` is connected to and `A` is 0.
+    ///
+    /// Returns an error if `wiring` isn't a permutation of `0..ALPHABET_SIZE`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::Rotor;
+    /// let mut wiring = [0; 26];
+    /// for (i, w) in wiring.iter_mut().enumerate() {
+    ///     *w = ((i + 1) % 26) as u8;
+    /// }
+    /// let rotor = Rotor::from_array(wiring, 0, 0, vec![25]).unwrap();
+    /// assert_eq!(rotor.forward_char('A'), 'B');
+    /// assert!(Rotor::from_array([0; 26], 0, 0, vec![25]).is_err());
+    /// ```
+    pub fn from_array(
+        forward_wiring: [u8; ALPHABET_SIZE],
+        ring_setting: usize,
+        position: usize,
+        notch_position: Vec<usize>,
+    ) -> Result<Self, InvalidArgsError> {
+        check_wiring(&forward_wiring)?;
+        Ok(Self::from_checked_array(
             forward_wiring,
-            backward_wiring,
+            ring_setting,
             position,
+            notch_position,
+        ))
+    }
+
+    /// Creates a new [`Rotor`] like [`Rotor::from_array`] from a slice of wire numbers, e.g. read
+    /// from a binary key format.
+    ///
+    /// Returns an error unless `wiring` has [`ALPHABET_SIZE`] entries and they're a permutation of
+    /// `0..ALPHABET_SIZE`.
+    pub fn from_bytes(
+        wiring: &[u8],
+        ring_setting: usize,
+        position: usize,
+        notch_position: Vec<usize>,
+    ) -> Result<Self, InvalidArgsError> {
+        let forward_wiring = wiring_from_bytes(wiring)?;
+        Ok(Self::from_checked_array(
+            forward_wiring,
             ring_setting,
+            position,
             notch_position,
+        ))
+    }
+
+    /// Creates a new [`Rotor`] from a wiring that's already known to be a permutation.
+    fn from_checked_array(
+        forward_wiring: [u8; ALPHABET_SIZE],
+        ring_setting: usize,
+        position: usize,
+        notch_position: Vec<usize>,
+    ) -> Self {
+        // The inverse is only computed here, so stepping and enciphering never rebuild it.
+        Self {
+            forward_wiring,
+            backward_wiring: invert_wiring(&forward_wiring),
+            position: position.into(),
+            ring_setting: ring_setting.into(),
+            notch_position: notch_position.into_iter().map(ClockInt::from).collect(),
         }
     }
 
-    rotor_cons!(i, "EKMFLGDQVZNTOWYHXUSPAIBRCJ", vec![16]);
-    rotor_cons!(ii, "AJDKSIRUXBLHWTMCQGZNPYFVOE", vec![4]);
-    rotor_cons!(iii, "BDFHJLCPRTXVZNYEIWGAKMUSQO", vec![21]);
-    rotor_cons!(iv, "ESOVPZJAYQUIRHXLNFTGKDCMWB", vec![9]);
-    rotor_cons!(v, "VZBRGITYUPSDNHLXAWMJQOFECK", vec![25]);
-    rotor_cons!(vi, "JPGVOUMFYQBENHZRDKASXLICTW", vec![12, 25]);
-    rotor_cons!(vii, "NZJHGRCXMYSWBOUFAIVLPEKQDT", vec![12, 25]);
-    rotor_cons!(viii, "FKQHTLXOCBJSPDZRAMEWNIUYGV", vec![12, 25]);
+    rotor_cons!(i, ROTOR_I, ROTOR_I_NOTCHES);
+    rotor_cons!(ii, ROTOR_II, ROTOR_II_NOTCHES);
+    rotor_cons!(iii, ROTOR_III, ROTOR_III_NOTCHES);
+    rotor_cons!(iv, ROTOR_IV, ROTOR_IV_NOTCHES);
+    rotor_cons!(v, ROTOR_V, ROTOR_V_NOTCHES);
+    rotor_cons!(vi, ROTOR_VI, ROTOR_VI_NOTCHES);
+    rotor_cons!(vii, ROTOR_VII, ROTOR_VII_NOTCHES);
+    rotor_cons!(viii, ROTOR_VIII, ROTOR_VIII_NOTCHES);
+    rotor_cons!(norway_i, NORWAY_I, NORWAY_I_NOTCHES);
+    rotor_cons!(norway_ii, NORWAY_II, NORWAY_II_NOTCHES);
+    rotor_cons!(norway_iii, NORWAY_III, NORWAY_III_NOTCHES);
+    rotor_cons!(norway_iv, NORWAY_IV, NORWAY_IV_NOTCHES);
+    rotor_cons!(norway_v, NORWAY_V, NORWAY_V_NOTCHES);
+    rotor_cons!(g_i, G_I, G_I_NOTCHES);
+    rotor_cons!(g_ii, G_II, G_II_NOTCHES);
+    rotor_cons!(g_iii, G_III, G_III_NOTCHES);
+    rotor_cons!(railway_i, RAILWAY_I, RAILWAY_I_NOTCHES);
+    rotor_cons!(railway_ii, RAILWAY_II, RAILWAY_II_NOTCHES);
+    rotor_cons!(railway_iii, RAILWAY_III, RAILWAY_III_NOTCHES);
 
-    pub fn from_name(name: &str, ring_setting: usize, position: usize) -> Self {
+    /// Creates the M4's Greek rotor beta, which has no notches.
+    pub fn beta(ring_setting: usize, position: usize) -> Self {
+        Self::new(wirings::BETA, ring_setting, position, Vec::new())
+    }
+
+    /// Creates the M4's Greek rotor gamma, which has no notches.
+    pub fn gamma(ring_setting: usize, position: usize) -> Self {
+        Self::new(wirings::GAMMA, ring_setting, position, Vec::new())
+    }
+
+    /// Names of the historical rotors accepted by [`Rotor::from_name`].
+    pub fn available_names() -> &'static [&'static str] {
+        &ROTOR_NAMES
+    }
+
+    /// Creates one of the historical rotors.
+    pub fn named(name: RotorName, ring_setting: usize, position: usize) -> Self {
         match name {
-            "I" => Self::i(ring_setting, position),
-            "II" => Self::ii(ring_setting, position),
-            "III" => Self::iii(ring_setting, position),
-            "IV" => Self::iv(ring_setting, position),
-            "V" => Self::v(ring_setting, position),
-            "VI" => Self::vi(ring_setting, position),
-            "VII" => Self::vii(ring_setting, position),
-            "VIII" => Self::viii(ring_setting, position),
-            _ => Self::default(),
+            RotorName::I => Self::i(ring_setting, position),
+            RotorName::II => Self::ii(ring_setting, position),
+            RotorName::III => Self::iii(ring_setting, position),
+            RotorName::IV => Self::iv(ring_setting, position),
+            RotorName::V => Self::v(ring_setting, position),
+            RotorName::VI => Self::vi(ring_setting, position),
+            RotorName::VII => Self::vii(ring_setting, position),
+            RotorName::VIII => Self::viii(ring_setting, position),
+            RotorName::NorwayI => Self::norway_i(ring_setting, position),
+            RotorName::NorwayII => Self::norway_ii(ring_setting, position),
+            RotorName::NorwayIII => Self::norway_iii(ring_setting, position),
+            RotorName::NorwayIV => Self::norway_iv(ring_setting, position),
+            RotorName::NorwayV => Self::norway_v(ring_setting, position),
+            RotorName::GI => Self::g_i(ring_setting, position),
+            RotorName::GII => Self::g_ii(ring_setting, position),
+            RotorName::GIII => Self::g_iii(ring_setting, position),
+            RotorName::RailwayI => Self::railway_i(ring_setting, position),
+            RotorName::RailwayII => Self::railway_ii(ring_setting, position),
+            RotorName::RailwayIII => Self::railway_iii(ring_setting, position),
+            RotorName::Beta => Self::beta(ring_setting, position),
+            RotorName::Gamma => Self::gamma(ring_setting, position),
         }
     }
 
+    /// Creates one of the historical rotors by name. Unknown names give the identity rotor.
+    pub fn from_name(name: &str, ring_setting: usize, position: usize) -> Self {
+        Self::try_from_name(name, ring_setting, position).unwrap_or_default()
+    }
+
+    /// Creates one of the historical rotors by name like [`Rotor::from_name`], returning an error
+    /// for unknown names.
+    pub fn try_from_name(
+        name: &str,
+        ring_setting: usize,
+        position: usize,
+    ) -> Result<Self, InvalidArgsError> {
+        Ok(Self::named(
+            RotorName::try_from(name)?,
+            ring_setting,
+            position,
+        ))
+    }
+
+    /// Current position, i.e. the letter showing in the machine's window.
+    pub fn position(&self) -> ClockInt {
+        self.position
+    }
+
+    /// Sets the position, e.g. `5`, `'F'` or a [`ClockInt`]. Numbers are reduced modulo
+    /// [`ALPHABET_SIZE`].
+    pub fn set_position(&mut self, position: impl Into<ClockInt>) {
+        self.position = position.into();
+    }
+
+    /// Ring setting, i.e. how far the alphabet ring is rotated relative to the wiring.
+    pub fn ring_setting(&self) -> ClockInt {
+        self.ring_setting
+    }
+
+    /// Sets the ring setting, e.g. `5`, `'F'` or a [`ClockInt`]. Numbers are reduced modulo
+    /// [`ALPHABET_SIZE`].
+    pub fn set_ring_setting(&mut self, ring_setting: impl Into<ClockInt>) {
+        self.ring_setting = ring_setting.into();
+    }
+
+    /// Whether the rotor is at a position where its next step also steps the rotor to its left.
     pub fn at_notch(&self) -> bool {
-        self.notch_position.iter().any(|&n| self.position == n)
+        self.notch_position.contains(&self.position)
     }
 
+    /// The notch positions as letters, in the order given to [`Rotor::new`], e.g. `['Q']` for rotor
+    /// I.
+    ///
+    /// Each is the letter showing in the window when the rotor's next step also steps the rotor to
+    /// its left, so rotor I turns over the next rotor as it moves from Q to R. This is the letter
+    /// usually listed as the turnover in reference tables. The notches are on the alphabet ring,
+    /// so the ring setting doesn't change these letters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::Rotor;
+    /// assert_eq!(Rotor::i(0, 0).notch_letters(), vec!['Q']);
+    /// assert_eq!(Rotor::vi(0, 0).notch_letters(), vec!['M', 'Z']);
+    /// ```
+    pub fn notch_letters(&self) -> Vec<char> {
+        self.notch_position
+            .iter()
+            .map(|&notch| wire_to_char(notch.into()))
+            .collect()
+    }
+
+    /// Steps the rotor on by one position.
     pub fn turnover(&mut self) {
-        self.position = (self.position + 1) % ALPHABET_SIZE;
+        self.position += ClockInt::new(1);
+    }
+
+    /// The wiring as the letters the contacts A-Z are connected to, as given to [`Rotor::new`].
+    /// Ignores the position and ring setting.
+    pub fn wiring_encoding(&self) -> String {
+        wiring_to_encoding(&self.forward_wiring)
+    }
+
+    /// The inverse of [`Rotor::wiring_encoding`], i.e. the contacts the letters A-Z are connected
+    /// to, as used on the way back from the reflector.
+    pub fn inverse_wiring_encoding(&self) -> String {
+        wiring_to_encoding(&self.backward_wiring)
+    }
+
+    /// The letter `c` comes out as when passed through the rotor towards the reflector, in its
+    /// current position. Doesn't step the rotor.
+    ///
+    /// `c` may be in either case and the result is uppercase.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `c` isn't an ASCII letter.
+    pub fn forward_char(&self, c: char) -> char {
+        map_char(c, |l| self.forward(l))
+    }
+
+    /// The letter `c` comes out as when passed back through the rotor from the reflector, in its
+    /// current position. See [`Rotor::forward_char`].
+    pub fn backward_char(&self, c: char) -> char {
+        map_char(c, |l| self.backward(l))
+    }
+
+    /// The whole substitution towards the reflector in the current position, as wire numbers
+    /// where `A` is 0, so `table[w]` is what [`Component::forward`] gives for `w`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::Rotor;
+    /// let table = Rotor::i(0, 1).forward_table();
+    /// // At B, A enters the wiring at B, which goes to K, and comes out one back at J.
+    /// assert_eq!(table[0], 9);
+    /// ```
+    pub fn forward_table(&self) -> [u8; ALPHABET_SIZE] {
+        self.table(&self.forward_wiring)
+    }
+
+    /// The whole substitution back from the reflector in the current position, the inverse of
+    /// [`Rotor::forward_table`].
+    pub fn backward_table(&self) -> [u8; ALPHABET_SIZE] {
+        self.table(&self.backward_wiring)
+    }
+
+    fn table(&self, wiring: &[u8; ALPHABET_SIZE]) -> [u8; ALPHABET_SIZE] {
+        let mut table = identity_wiring();
+        for t in table.iter_mut() {
+            *t = self.encipher(wiring, *t);
+        }
+        table
+    }
+
+    /// The name of the historical rotor with this wiring, or the wiring itself for a custom rotor.
+    pub(crate) fn name(&self) -> String {
+        RotorName::ALL
+            .into_iter()
+            .find(|&name| Self::named(name, 0, 0).forward_wiring == self.forward_wiring)
+            .map_or_else(|| self.wiring_encoding(), |name| name.as_str().to_string())
+    }
+
+    /// An equivalent rotor in the left-most slot, where the notches never matter and the ring
+    /// setting and position only matter through their difference. Used by
+    /// [`Machine::canonical_key`](crate::Machine::canonical_key).
+    pub(crate) fn canonical_left(&self) -> Self {
+        Self {
+            position: self.position - self.ring_setting,
+            ring_setting: ClockInt::default(),
+            notch_position: Vec::new(),
+            ..self.clone()
+        }
     }
 
-    fn encipher(&self, wiring: [u8; ALPHABET_SIZE], letter: u8) -> u8 {
-        let shift = self.position as isize - self.ring_setting as isize;
-        ((wiring[((letter as isize + shift + 26) % 26) as usize] as isize - shift + 26) % 26) as u8
+    fn encipher(&self, wiring: &[u8; ALPHABET_SIZE], letter: u8) -> u8 {
+        let shift = self.position - self.ring_setting;
+        let contact = ClockInt::from(letter) + shift;
+        (ClockInt::from(lookup(wiring, contact.value())) - shift).into()
     }
+}
 
-    pub fn forward(&self, letter: u8) -> u8 {
+impl Component for Rotor {
+    fn forward(&self, letter: u8) -> u8 {
         debug_assert!((letter as usize) < ALPHABET_SIZE);
-        self.encipher(self.forward_wiring, letter)
+        self.encipher(&self.forward_wiring, letter)
     }
 
-    pub fn backward(&self, letter: u8) -> u8 {
+    fn backward(&self, letter: u8) -> u8 {
         debug_assert!((letter as usize) < ALPHABET_SIZE);
-        self.encipher(self.backward_wiring, letter)
+        self.encipher(&self.backward_wiring, letter)
     }
 }
 
@@ -92,96 +604,439 @@ impl Default for Rotor {
         Self {
             forward_wiring: identity_wiring(),
             backward_wiring: identity_wiring(),
-            position: 0,
-            ring_setting: 0,
-            notch_position: vec![0],
+            position: ClockInt::default(),
+            ring_setting: ClockInt::default(),
+            notch_position: vec![ClockInt::default()],
         }
     }
 }
 
-pub(crate) struct Plugboard {
-    wiring: [u8; ALPHABET_SIZE],
+/// The plugboard in front of the rotors, which swaps pairs of letters on the way in and out.
+///
+/// The wiring in each direction is kept separately, though for ordinary pairs of plugs they're
+/// the same, so non-reciprocal connections like the Uhr's can be supported.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Plugboard {
+    forward_wiring: [u8; ALPHABET_SIZE],
+    backward_wiring: [u8; ALPHABET_SIZE],
 }
 
 impl Plugboard {
-    pub fn from_connections(connections: impl Into<String>) -> Self {
-        let mut wiring = identity_wiring();
-        let connections: String = connections.into();
+    /// Creates a [`Plugboard`] with no plugs, connecting each letter to itself. This is the same
+    /// as parsing empty connections, but says that no plugboard is intended.
+    pub fn identity() -> Self {
+        Self::from_wiring(identity_wiring())
+    }
 
-        let mut seen: HashSet<u8> = HashSet::new();
+    /// Creates a new [`Plugboard`] from letter pairs to swap, either space-separated (`"AB CD"`)
+    /// or concatenated (`"ABCD"`). The two forms can be mixed.
+    ///
+    /// Returns an error if a group of letters has odd length or contains anything other than ASCII
+    /// letters, or if a letter is connected more than once.
+    pub fn new(connections: &str) -> Result<Self, InvalidArgsError> {
+        let (plugboard, errors) = Self::parse(connections);
+        match errors.into_iter().next() {
+            Some(e) => Err(e),
+            None => Ok(plugboard),
+        }
+    }
 
-        for char_pair in connections.split_whitespace() {
-            let mut char_pair = char_pair.chars();
-            let char1 = char_pair.next();
-            let char2 = char_pair.next();
+    /// Parses `connections` like [`Plugboard::new`], carrying on past invalid groups and repeated
+    /// letters to collect every error, in the order they appear.
+    pub(crate) fn parse(connections: &str) -> (Self, Vec<InvalidArgsError>) {
+        let mut wiring = identity_wiring();
+        let mut seen: HashSet<u8> = HashSet::new();
+        let mut errors = Vec::new();
 
-            if char1.is_none() || char2.is_none() {
+        for group in connections.split_whitespace() {
+            if group.len() % 2 != 0 || !group.chars().all(|c| c.is_ascii_alphabetic()) {
+                errors.push(InvalidArgsError::InvalidPlugboard(group.to_string()));
                 continue;
             }
 
-            let char1 = char_to_wire(char1.unwrap());
-            let char2 = char_to_wire(char2.unwrap());
+            for pair in group.as_bytes().chunks_exact(2) {
+                let char1 = char_to_wire(pair[0] as char);
+                let char2 = char_to_wire(pair[1] as char);
+
+                let mut valid = true;
+                for c in [char1, char2] {
+                    if !seen.insert(c) {
+                        let error = InvalidArgsError::DuplicatePlug(wire_to_char(c));
+                        if !errors.contains(&error) {
+                            errors.push(error);
+                        }
+                        valid = false;
+                    }
+                }
 
-            if !seen.insert(char1) || !seen.insert(char2) {
-                panic!("Invalid connections")
+                if valid {
+                    wiring[char1 as usize] = char2;
+                    wiring[char2 as usize] = char1;
+                }
             }
+        }
+
+        (Self::from_wiring(wiring), errors)
+    }
 
-            wiring[char1 as usize] = char2;
-            wiring[char2 as usize] = char1;
+    /// Creates a new [`Plugboard`] with `forward_wiring` on the way in and its inverse on the way
+    /// out. `forward_wiring` must be a permutation.
+    fn from_wiring(forward_wiring: [u8; ALPHABET_SIZE]) -> Self {
+        Self {
+            forward_wiring,
+            backward_wiring: invert_wiring(&forward_wiring),
         }
+    }
+
+    /// Creates a new [`Plugboard`] like [`Plugboard::new`], returning the same errors.
+    pub fn from_connections(connections: impl Into<String>) -> Result<Self, InvalidArgsError> {
+        Self::new(&connections.into())
+    }
+
+    /// How many pairs of letters are swapped, from 0 up to 13.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::Plugboard;
+    /// let plugboard = Plugboard::new("AB CD FG").unwrap();
+    /// assert_eq!(plugboard.pair_count(), 3);
+    /// ```
+    pub fn pair_count(&self) -> usize {
+        let swapped = (0..ALPHABET_SIZE)
+            .filter(|&w| self.forward_wiring[w] as usize != w)
+            .count();
+        swapped / 2
+    }
 
-        Self { wiring }
+    /// The connections in the form [`Plugboard::new`] takes, as space-separated pairs in
+    /// alphabetical order, e.g. `"AB CD"`.
+    pub(crate) fn connections(&self) -> String {
+        (0..ALPHABET_SIZE as u8)
+            .filter(|&w| w < self.forward_wiring[w as usize])
+            .map(|w| {
+                format!(
+                    "{}{}",
+                    wire_to_char(w),
+                    wire_to_char(self.forward_wiring[w as usize])
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
     }
 
-    pub fn forward(&self, letter: u8) -> u8 {
+    /// The letter `c` is swapped with, or `c` itself if it isn't connected. See
+    /// [`Rotor::forward_char`] for how `c` is treated.
+    pub fn swap_char(&self, c: char) -> char {
+        map_char(c, |l| self.forward(l))
+    }
+}
+
+impl Component for Plugboard {
+    fn forward(&self, letter: u8) -> u8 {
+        debug_assert!((letter as usize) < ALPHABET_SIZE);
+        lookup(&self.forward_wiring, letter as usize)
+    }
+
+    fn backward(&self, letter: u8) -> u8 {
         debug_assert!((letter as usize) < ALPHABET_SIZE);
-        self.wiring[letter as usize]
+        lookup(&self.backward_wiring, letter as usize)
     }
 }
 
 impl Default for Plugboard {
     fn default() -> Self {
-        Self {
-            wiring: identity_wiring(),
-        }
+        Self::identity()
     }
 }
 
-pub(crate) struct Reflector {
+/// The fixed wheel at the end of the rotors that sends each letter back through them, pairing up
+/// the letters.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Reflector {
     wiring: [u8; ALPHABET_SIZE],
+    position: ClockInt,
 }
 
 impl Reflector {
+    /// Creates a new [`Reflector`] from its wiring, given as the letters A-Z are connected to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `encoding` isn't a permutation of A-Z. See [`Reflector::try_from_encoding`].
     pub fn from_encoding(encoding: impl Into<String>) -> Self {
-        Self {
-            wiring: encoding_to_wiring(encoding),
+        Self::try_from_encoding(encoding).expect("invalid reflector wiring")
+    }
+
+    /// Creates a new [`Reflector`] like [`Reflector::from_encoding`], returning an error if
+    /// `encoding` isn't a permutation of A-Z.
+    pub fn try_from_encoding(encoding: impl Into<String>) -> Result<Self, InvalidArgsError> {
+        Ok(Self {
+            wiring: try_encoding_to_wiring(encoding)?,
+            position: ClockInt::default(),
+        })
+    }
+
+    /// Creates a new [`Reflector`] from a wiring that's already been converted to wires, where
+    /// `wiring[i]` is the wire contact `i` is connected to and `A` is 0.
+    ///
+    /// Unlike [`Reflector::try_from_encoding`] this also checks that the wiring is one a
+    /// reflector could have: each letter must be paired with a different one, which is paired back
+    /// with it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::Reflector;
+    /// let mut wiring = [0; 26];
+    /// for (i, w) in wiring.iter_mut().enumerate() {
+    ///     *w = (25 - i) as u8;
+    /// }
+    /// let reflector = Reflector::from_array(wiring).unwrap();
+    /// assert_eq!(reflector.reflect_char('B'), 'Y');
+    ///
+    /// wiring.swap(0, 1);
+    /// assert!(Reflector::from_array(wiring).is_err());
+    /// ```
+    pub fn from_array(wiring: [u8; ALPHABET_SIZE]) -> Result<Self, InvalidArgsError> {
+        check_wiring(&wiring)?;
+        for (a, &b) in wiring.iter().enumerate() {
+            let a = a as u8;
+            if a == b {
+                return Err(InvalidArgsError::InvalidReflectorPairs(format!(
+                    "'{}' is paired with itself",
+                    wire_to_char(a)
+                )));
+            }
+            if wiring[b as usize] != a {
+                return Err(InvalidArgsError::InvalidReflectorPairs(format!(
+                    "'{}' is wired to '{}', which isn't wired back to it",
+                    wire_to_char(a),
+                    wire_to_char(b)
+                )));
+            }
+        }
+
+        Ok(Self {
+            wiring,
+            position: ClockInt::default(),
+        })
+    }
+
+    /// Creates a new [`Reflector`] like [`Reflector::from_array`] from a slice of wire numbers,
+    /// e.g. read from a binary key format.
+    ///
+    /// Returns an error unless `wiring` has [`ALPHABET_SIZE`] entries and they pair up the
+    /// letters like [`Reflector::from_array`] checks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::Reflector;
+    /// let bytes: Vec<u8> = (0..26).rev().collect();
+    /// let reflector = Reflector::from_bytes(&bytes).unwrap();
+    /// assert_eq!(reflector.reflect_char('A'), 'Z');
+    /// ```
+    pub fn from_bytes(wiring: &[u8]) -> Result<Self, InvalidArgsError> {
+        Self::from_array(wiring_from_bytes(wiring)?)
+    }
+
+    /// Creates a new [`Reflector`] from the 13 pairs of letters it connects, in either case.
+    ///
+    /// Returns an error unless the pairs cover every letter exactly once, so there's no need to
+    /// check the wiring like [`Reflector::try_from_encoding`] does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::Reflector;
+    /// let pairs: Vec<(char, char)> = ('A'..='M').zip(('N'..='Z').rev()).collect();
+    /// let reflector = Reflector::from_pairs(&pairs).unwrap();
+    /// assert_eq!(reflector.reflect_char('A'), 'Z');
+    /// assert_eq!(reflector.reflect_char('Z'), 'A');
+    /// assert!(Reflector::from_pairs(&pairs[1..]).is_err());
+    /// ```
+    pub fn from_pairs(pairs: &[(char, char)]) -> Result<Self, InvalidArgsError> {
+        if pairs.len() != ALPHABET_SIZE / 2 {
+            return Err(InvalidArgsError::InvalidReflectorPairs(format!(
+                "expected {} pairs, found {}",
+                ALPHABET_SIZE / 2,
+                pairs.len()
+            )));
+        }
+
+        let mut wiring = identity_wiring();
+        let mut seen = [false; ALPHABET_SIZE];
+        for &(a, b) in pairs {
+            for c in [a, b] {
+                if !c.is_ascii_alphabetic() {
+                    return Err(InvalidArgsError::InvalidReflectorPairs(format!(
+                        "'{}' isn't a letter",
+                        c
+                    )));
+                }
+            }
+            let (a, b) = (char_to_wire(a), char_to_wire(b));
+            if a == b {
+                return Err(InvalidArgsError::InvalidReflectorPairs(format!(
+                    "'{}' is paired with itself",
+                    wire_to_char(a)
+                )));
+            }
+            for w in [a, b] {
+                if std::mem::replace(&mut seen[w as usize], true) {
+                    return Err(InvalidArgsError::InvalidReflectorPairs(format!(
+                        "'{}' is paired more than once",
+                        wire_to_char(w)
+                    )));
+                }
+            }
+
+            wiring[a as usize] = b;
+            wiring[b as usize] = a;
         }
+
+        Ok(Self {
+            wiring,
+            position: ClockInt::default(),
+        })
     }
 
+    /// Creates the historical reflector A.
     pub fn a() -> Self {
-        Self::from_encoding("EJMZALYXVBWFCRQUONTSPIKHGD")
+        Self::from_encoding(wirings::REFLECTOR_A)
     }
 
+    /// Creates the historical reflector B.
     pub fn b() -> Self {
-        Self::from_encoding("YRUHQSLDPXNGOKMIEBFZCWVJAT")
+        Self::from_encoding(wirings::REFLECTOR_B)
     }
 
+    /// Creates the historical reflector C.
     pub fn c() -> Self {
-        Self::from_encoding("FVPJIAOYEDRZXWGCTKUQSBNMHL")
+        Self::from_encoding(wirings::REFLECTOR_C)
+    }
+
+    /// Creates the rewirable reflector D, UKW-D, also fitted to the Enigma KD, with a default set
+    /// of wires.
+    ///
+    /// Only the pair `J-Y`, in Bletchley Park's lettering, was fixed; the other 12 pairs were
+    /// plugged by the operator and changed with the key. This sets them to
+    /// `AV BO CT DM EZ FN GX HQ IS KR LU PW`, see [`wirings::REFLECTOR_D`], as a starting point.
+    /// Use [`Reflector::from_pairs`] to wire it for a particular key.
+    pub fn kd_default() -> Self {
+        Self::from_encoding(wirings::REFLECTOR_D)
+    }
+
+    /// Creates the reflector of the Norway Enigma, used with the [`RotorFamily::Norway`] rotors.
+    pub fn norway() -> Self {
+        Self::from_encoding(wirings::REFLECTOR_NORWAY)
+    }
+
+    /// Creates the M4's thin reflector B, used with the Greek rotor beta.
+    pub fn b_thin() -> Self {
+        Self::from_encoding(wirings::REFLECTOR_B_THIN)
+    }
+
+    /// Creates the M4's thin reflector C, used with the Greek rotor gamma.
+    pub fn c_thin() -> Self {
+        Self::from_encoding(wirings::REFLECTOR_C_THIN)
+    }
+
+    /// Creates the reflector of the Enigma G-312, used with the [`RotorFamily::G`] rotors.
+    pub fn g() -> Self {
+        Self::from_encoding(wirings::REFLECTOR_G)
+    }
+
+    /// Creates the reflector of the railway Enigma, used with the [`RotorFamily::Railway`]
+    /// rotors.
+    pub fn railway() -> Self {
+        Self::from_encoding(wirings::REFLECTOR_RAILWAY)
+    }
+
+    /// Names of the historical reflectors accepted by [`Reflector::from_name`].
+    pub fn available_names() -> &'static [&'static str] {
+        &REFLECTOR_NAMES
     }
 
+    /// Creates one of the historical reflectors by name. Unknown names give the identity
+    /// reflector.
     pub fn from_name(name: &str) -> Self {
+        Self::try_from_name(name).unwrap_or_default()
+    }
+
+    /// Creates one of the historical reflectors by name like [`Reflector::from_name`], returning
+    /// an error for unknown names, or [`InvalidArgsError::Empty`] if `name` is empty or only
+    /// whitespace.
+    pub fn try_from_name(name: &str) -> Result<Self, InvalidArgsError> {
         match name {
-            "A" => Self::a(),
-            "B" => Self::b(),
-            "C" => Self::c(),
-            _ => Self::default(),
+            _ if name.trim().is_empty() => Err(InvalidArgsError::Empty("reflector")),
+            "A" => Ok(Self::a()),
+            "B" => Ok(Self::b()),
+            "C" => Ok(Self::c()),
+            "D" => Ok(Self::kd_default()),
+            "NORWAY" => Ok(Self::norway()),
+            "G" => Ok(Self::g()),
+            "RAILWAY" => Ok(Self::railway()),
+            "B-THIN" => Ok(Self::b_thin()),
+            "C-THIN" => Ok(Self::c_thin()),
+            _ => Err(InvalidArgsError::InvalidReflectorName(name.to_string())),
         }
     }
 
-    pub fn forward(&self, letter: u8) -> u8 {
+    /// The name of the historical reflector with this wiring, or the wiring itself for a custom
+    /// reflector.
+    pub(crate) fn name(&self) -> String {
+        REFLECTOR_NAMES
+            .into_iter()
+            .find(|&name| Self::from_name(name).wiring == self.wiring)
+            .map_or_else(|| wiring_to_encoding(&self.wiring), str::to_string)
+    }
+
+    /// Current position. Only the Enigma G's reflector could be turned, and it also stepped as
+    /// the message was typed. The others always stay at `A`.
+    pub fn position(&self) -> ClockInt {
+        self.position
+    }
+
+    /// Sets the position, e.g. `5`, `'F'` or a [`ClockInt`]. Numbers are reduced modulo
+    /// [`ALPHABET_SIZE`].
+    pub fn set_position(&mut self, position: impl Into<ClockInt>) {
+        self.position = position.into();
+    }
+
+    /// Turns the reflector on by one position, as the Enigma G's gearing did when every rotor
+    /// was at a notch. [`Machine`](crate::Machine) only does this for
+    /// [`EnigmaModel::G`](crate::EnigmaModel::G).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::{ClockInt, Reflector};
+    /// let mut reflector = Reflector::g();
+    /// reflector.rotate();
+    /// assert_eq!(reflector.position(), ClockInt::new(1));
+    /// ```
+    pub fn rotate(&mut self) {
+        self.position += ClockInt::new(1);
+    }
+
+    /// The letter `c` is paired with. See [`Rotor::forward_char`] for how `c` is treated.
+    pub fn reflect_char(&self, c: char) -> char {
+        map_char(c, |l| self.forward(l))
+    }
+}
+
+impl Component for Reflector {
+    fn forward(&self, letter: u8) -> u8 {
         debug_assert!((letter as usize) < ALPHABET_SIZE);
-        self.wiring[letter as usize]
+        let contact = ClockInt::from(letter) + self.position;
+        let out = ClockInt::from(lookup(&self.wiring, contact.value())) - self.position;
+        out.into()
+    }
+
+    fn backward(&self, letter: u8) -> u8 {
+        self.forward(letter)
     }
 }
 
@@ -189,6 +1044,7 @@ impl Default for Reflector {
     fn default() -> Self {
         Self {
             wiring: identity_wiring(),
+            position: ClockInt::default(),
         }
     }
 }
@@ -196,6 +1052,17 @@ impl Default for Reflector {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_lookup() {
+        for encoding in [wirings::ROTOR_I, wirings::ROTOR_VIII, wirings::REFLECTOR_B] {
+            let wiring = crate::encoding_to_wiring(encoding);
+            for i in 0..ALPHABET_SIZE {
+                assert_eq!(lookup(&wiring, i), wiring[i]);
+            }
+        }
+    }
 
     #[test]
     fn test_rotor_wiring() {
@@ -203,25 +1070,309 @@ mod tests {
         for i in 0..26 {
             assert_eq!(rotor.backward(rotor.forward(i)), i);
         }
+
+        let plugboard = Plugboard::new("AV BS CG DL FU HZ IN KM OW RX").unwrap();
+        for i in 0..26 {
+            assert_eq!(plugboard.backward(plugboard.forward(i)), i);
+        }
+    }
+
+    #[test]
+    fn test_kd_default() {
+        let reflector = Reflector::from_name("D");
+        assert_eq!(reflector, Reflector::kd_default());
+        assert_eq!(
+            Reflector::from_array(reflector.wiring),
+            Ok(reflector.clone())
+        );
+        assert_eq!(reflector.reflect_char('J'), 'Y');
+        assert_eq!(reflector.reflect_char('Y'), 'J');
+    }
+
+    #[test]
+    fn test_named_components() {
+        for name in ROTOR_NAMES {
+            assert_ne!(
+                Rotor::from_name(name, 0, 0).forward_wiring,
+                identity_wiring()
+            );
+        }
+        for name in REFLECTOR_NAMES {
+            assert_ne!(Reflector::from_name(name).wiring, identity_wiring());
+        }
+    }
+
+    #[test]
+    fn test_component_names() {
+        for name in ROTOR_NAMES {
+            // Norway rotor IV is wired like rotor IV, so either name will do.
+            let rotor = Rotor::from_name(name, 3, 5);
+            assert_eq!(Rotor::from_name(&rotor.name(), 3, 5), rotor);
+        }
+        for name in REFLECTOR_NAMES {
+            assert_eq!(Reflector::from_name(name).name(), name);
+        }
+        let custom = "BCDEFGHIJKLMNOPQRSTUVWXYZA";
+        assert_eq!(Rotor::new(custom, 0, 0, vec![]).name(), custom);
+        assert_eq!(
+            Plugboard::from_connections("ZA QBDC")
+                .unwrap()
+                .connections(),
+            "AZ BQ CD"
+        );
+        assert_eq!(Plugboard::default().connections(), "");
+    }
+
+    #[test]
+    fn test_rotor_names() {
+        for name in RotorName::ALL {
+            assert_eq!(RotorName::try_from(name.as_str()), Ok(name));
+            assert_eq!(name.to_string(), name.as_str());
+            assert_eq!(
+                Rotor::from_name(name.as_str(), 0, 0),
+                Rotor::named(name, 0, 0)
+            );
+        }
+        assert_eq!(Rotor::available_names().len(), RotorName::ALL.len());
+        assert_eq!(RotorName::try_from("NORWAY-III"), Ok(RotorName::NorwayIII));
+        assert_eq!(
+            RotorName::try_from("IX"),
+            Err(InvalidArgsError::InvalidRotorName("IX".to_string()))
+        );
+        for name in ["", " ", "\t\n"] {
+            assert_eq!(
+                RotorName::try_from(name),
+                Err(InvalidArgsError::Empty("rotor"))
+            );
+            assert_eq!(
+                Rotor::try_from_name(name, 0, 0),
+                Err(InvalidArgsError::Empty("rotor"))
+            );
+            assert_eq!(
+                Reflector::try_from_name(name),
+                Err(InvalidArgsError::Empty("reflector"))
+            );
+        }
+        assert_eq!(
+            InvalidArgsError::Empty("rotor").to_string(),
+            "no rotor specified"
+        );
+
+        assert_eq!(RotorName::VIII.family(), RotorFamily::Wehrmacht);
+        assert_eq!(RotorName::NorwayI.family(), RotorFamily::Norway);
+        assert_ne!(Rotor::norway_i(0, 0), Rotor::i(0, 0));
+    }
+
+    #[test]
+    fn test_set_position_and_ring_setting() {
+        let mut rotor = Rotor::i(0, 0);
+        rotor.set_position(ClockInt::new(2));
+        rotor.set_ring_setting(27usize);
+        assert_eq!(rotor, Rotor::i(1, 2));
+        assert_eq!(rotor.position(), ClockInt::new(2));
+        assert_eq!(rotor.ring_setting(), ClockInt::new(1));
+
+        rotor.set_position(ClockInt::new(16));
+        assert!(rotor.at_notch());
+
+        // The ring setting comes first, as in Rotor::i.
+        assert_eq!(Rotor::new(wirings::ROTOR_I, 1, 0, vec![16]), Rotor::i(1, 0));
+        assert_ne!(Rotor::new(wirings::ROTOR_I, 0, 1, vec![16]), Rotor::i(1, 0));
+    }
+
+    #[test]
+    fn test_wiring_encodings() {
+        let rotor = Rotor::i(3, 5);
+        assert_eq!(rotor.wiring_encoding(), wirings::ROTOR_I);
+        assert_eq!(
+            rotor.inverse_wiring_encoding(),
+            "UWYGADFPVZBECKMTHXSLRINQOJ"
+        );
+        let inverse = Rotor::new(rotor.inverse_wiring_encoding(), 0, 0, vec![]);
+        assert_eq!(inverse.inverse_wiring_encoding(), wirings::ROTOR_I);
+    }
+
+    #[test]
+    fn test_char_methods() {
+        let rotor = Rotor::i(0, 0);
+        assert_eq!(rotor.forward_char('A'), 'E');
+        assert_eq!(rotor.forward_char('b'), 'K');
+        assert_eq!(rotor.backward_char('E'), 'A');
+        assert_eq!(rotor.position(), ClockInt::default());
+        assert_eq!(Rotor::i(0, 1).forward_char('A'), 'J');
+
+        assert_eq!(Reflector::b().reflect_char('A'), 'Y');
+        assert_eq!(Reflector::b().reflect_char('Y'), 'A');
+
+        let plugboard = Plugboard::new("AB").unwrap();
+        assert_eq!(plugboard.swap_char('A'), 'B');
+        assert_eq!(plugboard.swap_char('b'), 'A');
+        assert_eq!(plugboard.swap_char('C'), 'C');
+    }
+
+    #[test]
+    #[should_panic(expected = "'1' is not an ASCII letter")]
+    fn test_char_methods_non_letter() {
+        Rotor::i(0, 0).forward_char('1');
+    }
+
+    #[test]
+    fn test_notch_letters() {
+        for (name, notches) in [
+            ("I", "Q"),
+            ("II", "E"),
+            ("III", "V"),
+            ("IV", "J"),
+            ("V", "Z"),
+        ] {
+            let mut rotor = Rotor::from_name(name, 0, 0);
+            assert_eq!(rotor.notch_letters(), notches.chars().collect::<Vec<_>>());
+
+            // The rotor is at its notch when the window shows the notch letter.
+            rotor.set_position(ClockInt::try_from(rotor.notch_letters()[0]).unwrap());
+            assert!(rotor.at_notch());
+            rotor.turnover();
+            assert!(!rotor.at_notch());
+        }
+        assert!(Rotor::beta(0, 0).notch_letters().is_empty());
+
+        // The Kriegsmarine rotors have two notches, whatever the ring setting.
+        for rotor in [Rotor::vi, Rotor::vii, Rotor::viii] {
+            assert_eq!(rotor(0, 0).notch_letters(), vec!['M', 'Z']);
+            assert_eq!(rotor(7, 3).notch_letters(), vec!['M', 'Z']);
+        }
+    }
+
+    #[test]
+    fn test_double_notches() {
+        for rotor in [Rotor::vi, Rotor::vii, Rotor::viii] {
+            for position in 0..26 {
+                let at_notch = rotor(0, position).at_notch();
+                assert_eq!(at_notch, position == 12 || position == 25);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_wiring() {
+        assert!(Rotor::try_new("EKMFLGDQVZNTOWYHXUSPAIBRCJ", 0, 0, vec![16]).is_ok());
+        assert_eq!(
+            Rotor::try_new(wirings::ROTOR_I, 1, 0, vec![16]),
+            Ok(Rotor::i(1, 0))
+        );
+        assert_ne!(
+            Rotor::try_new(wirings::ROTOR_I, 0, 1, vec![16]),
+            Ok(Rotor::i(1, 0))
+        );
+        assert_eq!(
+            Rotor::try_new("EKMFLGDQVZNTOWYHXUSPAIBRCE", 0, 0, vec![16]),
+            Err(InvalidArgsError::InvalidWiring(
+                "EKMFLGDQVZNTOWYHXUSPAIBRCE".to_string()
+            ))
+        );
+        assert!(Reflector::try_from_encoding("AAAAAAAAAAAAAAAAAAAAAAAAAA").is_err());
     }
 
     #[test]
     fn test_plugboard_build_ident() {
-        let plug = Plugboard::from_connections("");
+        let plug = Plugboard::from_connections("").unwrap();
         let expected = identity_wiring();
-        assert_eq!(plug.wiring, expected);
+        assert_eq!(plug.forward_wiring, expected);
+
+        assert_eq!(Plugboard::identity().forward_wiring, expected);
+        assert_eq!(Plugboard::identity().backward_wiring, expected);
+        for connections in ["", " ", "\t\n"] {
+            assert_eq!(Plugboard::new(connections), Ok(Plugboard::identity()));
+        }
     }
 
     #[test]
     fn test_plugboard_build() {
-        let plug = Plugboard::from_connections("AB DE");
+        let plug = Plugboard::from_connections("AB DE").unwrap();
         let mut expected = identity_wiring();
         expected[0] = 1;
         expected[1] = 0;
         expected[3] = 4;
         expected[4] = 3;
 
-        assert_eq!(plug.wiring, expected);
+        assert_eq!(plug.forward_wiring, expected);
+    }
+
+    #[test]
+    fn test_plugboard_pair_count() {
+        assert_eq!(Plugboard::default().pair_count(), 0);
+        assert_eq!(Plugboard::new("AB").unwrap().pair_count(), 1);
+        assert_eq!(
+            Plugboard::new("AV BS CG DL FU HZ IN KM OW RX")
+                .unwrap()
+                .pair_count(),
+            10
+        );
+        assert_eq!(
+            Plugboard::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ")
+                .unwrap()
+                .pair_count(),
+            13
+        );
+        // The second use of A is dropped.
+        assert_eq!(Plugboard::parse("AB AC DE").0.pair_count(), 2);
+    }
+
+    #[test]
+    fn test_plugboard_reciprocal() {
+        for connections in ["", "AB DE", "AV BS CG DL FU HZ IN KM OW RX"] {
+            let plug = Plugboard::from_connections(connections).unwrap();
+            assert_eq!(plug.forward_wiring, plug.backward_wiring);
+            for w in 0..ALPHABET_SIZE as u8 {
+                assert_eq!(plug.forward(w), plug.backward(w));
+            }
+        }
+    }
+
+    #[test]
+    fn test_plugboard_from_connections_duplicate() {
+        assert_eq!(
+            Plugboard::from_connections("AB CA"),
+            Err(InvalidArgsError::DuplicatePlug('A'))
+        );
+        assert_eq!(
+            Plugboard::from_connections(String::from("ABCDEB")),
+            Err(InvalidArgsError::DuplicatePlug('B'))
+        );
+    }
+
+    #[test]
+    fn test_plugboard_concatenated() {
+        let expected = Plugboard::from_connections("AB DE").unwrap();
+        assert_eq!(Plugboard::new("ABDE"), Ok(expected.clone()));
+        assert_eq!(Plugboard::new("abde"), Ok(expected.clone()));
+        assert_eq!(Plugboard::new(" AB  DE "), Ok(expected.clone()));
+        assert_eq!(Plugboard::new("AB DE"), Ok(expected));
+        assert_eq!(Plugboard::new("ABDEFG HI"), Plugboard::new("AB DE FG HI"));
+    }
+
+    #[test]
+    fn test_plugboard_invalid() {
+        assert_eq!(
+            Plugboard::new("ABC"),
+            Err(InvalidArgsError::InvalidPlugboard("ABC".to_string()))
+        );
+        assert_eq!(
+            Plugboard::new("AB C"),
+            Err(InvalidArgsError::InvalidPlugboard("C".to_string()))
+        );
+        assert_eq!(
+            Plugboard::new("A1"),
+            Err(InvalidArgsError::InvalidPlugboard("A1".to_string()))
+        );
+        assert_eq!(
+            Plugboard::new("ABCA"),
+            Err(InvalidArgsError::DuplicatePlug('A'))
+        );
+        assert_eq!(
+            Plugboard::new("AA"),
+            Err(InvalidArgsError::DuplicatePlug('A'))
+        );
     }
 
     #[test]
@@ -243,4 +1394,204 @@ mod tests {
 
         assert_eq!(refl.wiring, expected);
     }
+
+    #[test]
+    fn test_reflector_from_pairs() {
+        let pairs: Vec<(char, char)> = wirings::REFLECTOR_B
+            .chars()
+            .zip('A'..='Z')
+            .filter(|(a, b)| a < b)
+            .collect();
+        assert_eq!(Reflector::from_pairs(&pairs), Ok(Reflector::b()));
+
+        let lowercase: Vec<(char, char)> = pairs
+            .iter()
+            .map(|&(a, b)| (a.to_ascii_lowercase(), b))
+            .collect();
+        assert_eq!(Reflector::from_pairs(&lowercase), Ok(Reflector::b()));
+
+        let error = |reason: &str| Err(InvalidArgsError::InvalidReflectorPairs(reason.into()));
+        assert_eq!(
+            Reflector::from_pairs(&pairs[..12]),
+            error("expected 13 pairs, found 12")
+        );
+
+        let mut invalid = pairs.clone();
+        invalid[0] = ('A', 'A');
+        assert_eq!(
+            Reflector::from_pairs(&invalid),
+            error("'A' is paired with itself")
+        );
+        invalid[0] = ('B', 'C');
+        assert_eq!(
+            Reflector::from_pairs(&invalid),
+            error("'B' is paired more than once")
+        );
+        invalid[0] = ('1', 'C');
+        assert_eq!(Reflector::from_pairs(&invalid), error("'1' isn't a letter"));
+    }
+
+    #[test]
+    fn test_rotor_tables() {
+        for name in ROTOR_NAMES {
+            for (ring_setting, position) in [(0, 0), (0, 5), (7, 3), (25, 24)] {
+                let rotor = Rotor::from_name(name, ring_setting, position);
+                let forward = rotor.forward_table();
+                let backward = rotor.backward_table();
+                for i in 0..ALPHABET_SIZE as u8 {
+                    assert_eq!(forward[i as usize], rotor.forward(i));
+                    assert_eq!(backward[i as usize], rotor.backward(i));
+                }
+                assert_eq!(invert_wiring(&forward), backward);
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_array() {
+        let rotor_wiring = crate::encoding_to_wiring(wirings::ROTOR_I);
+        assert_eq!(
+            Rotor::from_array(rotor_wiring, 5, 3, vec![16]),
+            Ok(Rotor::i(5, 3))
+        );
+        assert_ne!(
+            Rotor::from_array(rotor_wiring, 3, 5, vec![16]),
+            Ok(Rotor::i(5, 3))
+        );
+        let mut invalid = rotor_wiring;
+        invalid[0] = invalid[1];
+        assert!(matches!(
+            Rotor::from_array(invalid, 0, 0, vec![16]),
+            Err(InvalidArgsError::InvalidWiring(_))
+        ));
+        invalid[0] = 26;
+        assert!(Rotor::from_array(invalid, 0, 0, vec![16]).is_err());
+
+        let reflector_wiring = crate::encoding_to_wiring(wirings::REFLECTOR_B);
+        assert_eq!(Reflector::from_array(reflector_wiring), Ok(Reflector::b()));
+        // Permutations that aren't pairings are rejected, unlike with try_from_encoding.
+        assert_eq!(
+            Reflector::from_array(identity_wiring()),
+            Err(InvalidArgsError::InvalidReflectorPairs(
+                "'A' is paired with itself".to_string()
+            ))
+        );
+        assert_eq!(
+            Reflector::from_array(rotor_wiring),
+            Err(InvalidArgsError::InvalidReflectorPairs(
+                "'A' is wired to 'E', which isn't wired back to it".to_string()
+            ))
+        );
+        assert!(matches!(
+            Reflector::from_array([1; 26]),
+            Err(InvalidArgsError::InvalidWiring(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let rotor_wiring = crate::encoding_to_wiring(wirings::ROTOR_I);
+        assert_eq!(
+            Rotor::from_bytes(&rotor_wiring, 5, 3, vec![16]),
+            Ok(Rotor::i(5, 3))
+        );
+        assert_ne!(
+            Rotor::from_bytes(&rotor_wiring, 3, 5, vec![16]),
+            Ok(Rotor::i(5, 3))
+        );
+        assert!(Rotor::from_bytes(&rotor_wiring[1..], 0, 0, vec![]).is_err());
+
+        let reflector_wiring = crate::encoding_to_wiring(wirings::REFLECTOR_C);
+        assert_eq!(Reflector::from_bytes(&reflector_wiring), Ok(Reflector::c()));
+        assert!(Reflector::from_bytes(&reflector_wiring[..25]).is_err());
+        assert!(Reflector::from_bytes(&rotor_wiring).is_err());
+    }
+
+    #[test]
+    fn test_reflector_position() {
+        let mut refl = Reflector::g();
+        refl.set_position(ClockInt::new(1));
+        for c in 'A'..='Z' {
+            let reflected = refl.reflect_char(c);
+            assert_ne!(reflected, c);
+            assert_eq!(refl.reflect_char(reflected), c);
+        }
+        // Contact B is wired to U, which comes out at T with the reflector turned by one.
+        assert_eq!(refl.reflect_char('A'), 'T');
+
+        refl.rotate();
+        assert_eq!(refl.position(), ClockInt::new(2));
+    }
+
+    #[test]
+    fn test_reflector_rotate() {
+        // Turning the reflector by k conjugates its wiring by a shift of k.
+        let reference = |c: char, k: u8| {
+            let contact = (c as u8 - b'A' + k) % 26;
+            let wired = wirings::REFLECTOR_G.as_bytes()[contact as usize] - b'A';
+            ((wired + 26 - k) % 26 + b'A') as char
+        };
+
+        let mut refl = Reflector::g();
+        for k in 0..26 {
+            assert_eq!(refl.position(), ClockInt::new(k as usize));
+            for c in 'A'..='Z' {
+                assert_eq!(refl.reflect_char(c), reference(c, k));
+            }
+            refl.rotate();
+        }
+        assert_eq!(refl.position(), ClockInt::new(0));
+    }
+
+    proptest! {
+        // Both the ring setting and the position turn the wiring relative to the contacts, one
+        // each way, so only their difference matters to the substitution.
+        #[test]
+        fn prop_ring_setting_only_shifts(
+            name in proptest::sample::select(&ROTOR_NAMES[..]),
+            ring_setting in 0..26usize,
+            position in 0..26usize,
+            shift in 0..26usize,
+        ) {
+            let rotor = Rotor::from_name(name, ring_setting, position);
+            let shifted = Rotor::from_name(name, ring_setting + shift, position + shift);
+            for w in 0..ALPHABET_SIZE as u8 {
+                prop_assert_eq!(rotor.forward(w), shifted.forward(w));
+                prop_assert_eq!(rotor.backward(w), shifted.backward(w));
+                prop_assert_eq!(rotor.backward(rotor.forward(w)), w);
+            }
+        }
+
+        // Changing the ring setting alone rotates the whole substitution, keeping it a
+        // permutation.
+        #[test]
+        fn prop_ring_setting_keeps_permutation(
+            name in proptest::sample::select(&ROTOR_NAMES[..]),
+            ring_setting in 0..26usize,
+            position in 0..26usize,
+        ) {
+            let rotor = Rotor::from_name(name, ring_setting, position);
+            let mut seen = [false; ALPHABET_SIZE];
+            for w in 0..ALPHABET_SIZE as u8 {
+                let out = rotor.forward(w) as usize;
+                prop_assert!(out < ALPHABET_SIZE && !seen[out]);
+                seen[out] = true;
+            }
+        }
+
+        // Raw wiring bytes are checked rather than trusted, so a rotor built from them always
+        // has a permutation as its wiring.
+        #[test]
+        fn prop_from_bytes_never_panics(bytes in proptest::collection::vec(0..30u8, 0..30)) {
+            if let Ok(rotor) = Rotor::from_bytes(&bytes, 0, 0, vec![]) {
+                prop_assert_eq!(rotor.backward_table(), invert_wiring(&rotor.forward_table()));
+            }
+            if let Ok(reflector) = Reflector::from_bytes(&bytes) {
+                for w in 0..ALPHABET_SIZE as u8 {
+                    prop_assert_ne!(reflector.forward(w), w);
+                    prop_assert_eq!(reflector.forward(reflector.forward(w)), w);
+                }
+            }
+        }
+    }
 }
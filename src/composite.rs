@@ -0,0 +1,122 @@
+use crate::Machine;
+
+/// Two [`Machine`]s chained together, where each letter is enciphered by the first machine and
+/// then by the second, and the first machine's output gives the second extra steps.
+///
+/// This is a toy for experimenting with layering machines, not anything used historically. Every
+/// letter steps both machines as usual, and the second machine steps once more whenever the first
+/// machine's output is in the first half of the alphabet, `A-M`. That makes the stepping of the
+/// second machine depend on the message, so unlike a single machine the chain isn't its own
+/// inverse; use [`CompositeMachine::decrypt`] to undo [`CompositeMachine::encrypt`].
+///
+/// # Examples
+///
+/// ```
+/// use enigma::{CompositeMachine, Machine};
+/// let chain = || {
+///     CompositeMachine::new(
+///         Machine::default_m3(),
+///         Machine::new(("IV", "V", "VI"), (0, 0, 0), (0, 0, 0), "C", "AB CD"),
+///     )
+/// };
+/// let encrypted = chain().encrypt("HELLOXWORLD");
+/// assert_eq!(chain().decrypt(&encrypted), "HELLOXWORLD");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompositeMachine {
+    first: Machine,
+    second: Machine,
+}
+
+impl CompositeMachine {
+    /// Creates a new [`CompositeMachine`] enciphering with `first` and then `second`.
+    pub fn new(first: Machine, second: Machine) -> Self {
+        Self { first, second }
+    }
+
+    /// The machine each letter goes through first when encrypting.
+    pub fn first(&self) -> &Machine {
+        &self.first
+    }
+
+    /// The machine each letter goes through second when encrypting.
+    pub fn second(&self) -> &Machine {
+        &self.second
+    }
+
+    /// Encrypts a `message` through both machines. Characters that aren't ASCII letters are left
+    /// out without stepping either machine.
+    pub fn encrypt(&mut self, message: &str) -> String {
+        message
+            .chars()
+            .filter_map(|c| {
+                let middle = self.first.encrypt_char(c)?;
+                let encrypted = self.second.encrypt_char(middle)?;
+                self.step_second(middle);
+                Some(encrypted)
+            })
+            .collect()
+    }
+
+    /// Decrypts a `message` encrypted with [`CompositeMachine::encrypt`] by a chain set up the
+    /// same way, going through the machines in the opposite order. Characters that aren't ASCII
+    /// letters are left out without stepping either machine.
+    pub fn decrypt(&mut self, message: &str) -> String {
+        message
+            .chars()
+            .filter_map(|c| {
+                let middle = self.second.encrypt_char(c)?;
+                self.step_second(middle);
+                self.first.encrypt_char(middle)
+            })
+            .collect()
+    }
+
+    /// Gives the second machine its extra step if `middle`, the letter between the machines, is
+    /// in `A-M`. The step is a key press whose output is thrown away.
+    fn step_second(&mut self, middle: char) {
+        if middle <= 'M' {
+            self.second.encrypt_char('A');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain() -> CompositeMachine {
+        CompositeMachine::new(
+            Machine::new(("I", "II", "III"), (0, 0, 0), (0, 0, 0), "B", ""),
+            Machine::new(("IV", "V", "VI"), (3, 7, 11), (1, 2, 3), "C", "AZ BY"),
+        )
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let message = "THEXQUICKXBROWNXFOXXJUMPSXOVERXTHEXLAZYXDOG".repeat(10);
+        let encrypted = chain().encrypt(&message);
+        assert_ne!(encrypted, message);
+        assert_eq!(chain().decrypt(&encrypted), message);
+
+        assert_eq!(
+            chain().encrypt("Hello, world!"),
+            chain().encrypt("HELLOWORLD")
+        );
+    }
+
+    #[test]
+    fn test_extra_steps() {
+        // Without the extra steps the chain would be the two machines one after the other.
+        let mut chain = chain();
+        let (mut first, mut second) = (chain.first().clone(), chain.second().clone());
+        let message = "AAAAAAAAAAAAAAAAAAAA";
+        let middle = first.encrypt(message);
+        assert_ne!(chain.encrypt(message), second.clone().encrypt(&middle));
+
+        // One key press per letter, and one more for each letter in A-M between the machines.
+        let presses = message.len() + middle.chars().filter(|&c| c <= 'M').count();
+        second.encrypt(&"A".repeat(presses));
+        assert_eq!(chain.second().positions(), second.positions());
+    }
+}
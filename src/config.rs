@@ -0,0 +1,1066 @@
+use crate::components::{Plugboard, Reflector, Rotor};
+use crate::ClockInt;
+use crate::{
+    wire_to_char, DoubleStepMode, EnigmaModel, InvalidArgsError, Machine, RotorFamily, RotorName,
+    ALPHABET_SIZE,
+};
+use std::env::VarError;
+use std::str::FromStr;
+
+/// Builder for a [`Machine`] of any [`EnigmaModel`], which checks the settings against the model.
+///
+/// Rotors, ring settings and positions are listed from left to right, starting with the Greek
+/// rotor on the [`EnigmaModel::M4`].
+///
+/// # Examples
+///
+/// ```
+/// use enigma::{EnigmaModel, MachineConfig};
+/// let mut machine = MachineConfig::new(EnigmaModel::M4)
+///     .rotors(&["BETA", "II", "IV", "I"])
+///     .ring_settings(&[0, 0, 0, 21])
+///     .positions(&[21, 9, 13, 5])
+///     .reflector("B-THIN")
+///     .plugboard("AT BL DF GJ HM NW OP QY RZ VX")
+///     .build()
+///     .unwrap();
+/// let encrypted = machine.encrypt("HELLOXWORLD");
+/// assert_eq!(encrypted.len(), 11);
+/// ```
+///
+/// Configurations can be collected into a [`HashSet`](std::collections::HashSet) to drop
+/// duplicates. Two configurations are only equal if they're written the same way, so e.g. a
+/// position of 27 and a position of 1 are different here even though they build the same machine.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MachineConfig {
+    model: EnigmaModel,
+    rotors: Vec<String>,
+    ring_settings: Vec<usize>,
+    positions: Vec<usize>,
+    reflector: String,
+    reflector_position: usize,
+    plugboard: String,
+    notches: Option<Vec<Vec<usize>>>,
+    double_step: DoubleStepMode,
+}
+
+impl MachineConfig {
+    /// Creates a configuration for the given model, starting with its usual rotors and reflector,
+    /// ring settings and positions of 0 and no plugs.
+    pub fn new(model: EnigmaModel) -> Self {
+        let (rotors, reflector): (&[&str], _) = match model {
+            EnigmaModel::M3 => (&["I", "II", "III"], "B"),
+            EnigmaModel::M4 => (&["BETA", "I", "II", "III"], "B-THIN"),
+            EnigmaModel::G => (&["G-I", "G-II", "G-III"], "G"),
+            EnigmaModel::Railway => (&["RAILWAY-I", "RAILWAY-II", "RAILWAY-III"], "RAILWAY"),
+        };
+
+        Self {
+            model,
+            rotors: rotors.iter().map(|r| r.to_string()).collect(),
+            ring_settings: vec![0; model.rotor_count()],
+            positions: vec![0; model.rotor_count()],
+            reflector: reflector.to_string(),
+            reflector_position: 0,
+            plugboard: String::new(),
+            notches: None,
+            double_step: DoubleStepMode::default(),
+        }
+    }
+
+    /// Creates a random but valid [`EnigmaModel::M3`] configuration from `seed`, always the same
+    /// for the same seed, so keys and test fixtures can be shared as a number.
+    ///
+    /// Picks 3 different rotors from I-VIII, random ring settings and positions, reflector B or C
+    /// and 10 plugboard pairs, as in wartime use. The numbers come from SplitMix64, a small
+    /// non-cryptographic generator embedded here, so the keys aren't suitable for real secrecy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::MachineConfig;
+    /// assert_eq!(MachineConfig::seeded(42), MachineConfig::seeded(42));
+    /// let machine = MachineConfig::seeded(42).build().unwrap();
+    /// ```
+    pub fn seeded(seed: u64) -> Self {
+        let mut rng = SplitMix64(seed);
+
+        let mut rotors: Vec<&str> = RotorName::ALL
+            .iter()
+            .filter(|r| r.family() == RotorFamily::Wehrmacht)
+            .map(|r| r.as_str())
+            .collect();
+        rng.shuffle(&mut rotors);
+        rotors.truncate(3);
+
+        let ring_settings: Vec<usize> = (0..3).map(|_| rng.below(ALPHABET_SIZE)).collect();
+        let positions: Vec<usize> = (0..3).map(|_| rng.below(ALPHABET_SIZE)).collect();
+        let reflector = ["B", "C"][rng.below(2)];
+
+        let mut letters: Vec<char> = ('A'..='Z').collect();
+        rng.shuffle(&mut letters);
+        let plugboard = letters[..20]
+            .chunks_exact(2)
+            .map(|pair| pair.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Self::new(EnigmaModel::M3)
+            .rotors(&rotors)
+            .ring_settings(&ring_settings)
+            .positions(&positions)
+            .reflector(reflector)
+            .plugboard(&plugboard)
+    }
+
+    /// Parses a line of a daily key sheet, made of whitespace-separated columns in this order:
+    ///
+    /// 1. The day of the month, `1`-`31`. It's only checked, as the machine has no use for it.
+    /// 2. The rotor order, as 3 rotor names, or 4 for the [`EnigmaModel::M4`].
+    /// 3. A ring setting for each rotor, numbered `01`-`26` as on the sheet, so `01` is `A`.
+    /// 4. Any number of plugboard pairs, e.g. `AD CN ET`.
+    /// 5. The reflector name.
+    ///
+    /// For example `31 I IV III 16 26 08 AD CN ET FL GI JV KZ PU QY WX B`. The sheet doesn't give
+    /// the rotor positions, so they're left at 0 to be set from the indicator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::MachineConfig;
+    /// let config = MachineConfig::from_keysheet_line("31 I IV III 16 26 08 AD CN ET B").unwrap();
+    /// assert_eq!(
+    ///     config,
+    ///     MachineConfig::default()
+    ///         .rotors(&["I", "IV", "III"])
+    ///         .ring_settings(&[15, 25, 7])
+    ///         .plugboard("AD CN ET")
+    /// );
+    /// ```
+    pub fn from_keysheet_line(line: &str) -> Result<Self, InvalidArgsError> {
+        let malformed = |reason: String| {
+            InvalidArgsError::InvalidKey(format!("key sheet line '{}': {}", line.trim(), reason))
+        };
+        let is_number = |column: &str| column.bytes().all(|b| b.is_ascii_digit());
+
+        let mut columns = line.split_whitespace();
+        let day = columns.next().unwrap_or_default();
+        if !matches!(day.parse(), Ok(1..=31)) {
+            return Err(malformed(format!("day '{}' is not a number 1-31", day)));
+        }
+
+        let columns: Vec<&str> = columns.collect();
+        let rotor_count = columns.iter().take_while(|c| !is_number(c)).count();
+        let (rotors, columns) = columns.split_at(rotor_count);
+        let model = match rotors.len() {
+            3 => EnigmaModel::M3,
+            4 => EnigmaModel::M4,
+            found => {
+                return Err(malformed(format!(
+                    "expected 3 or 4 rotors, found {}",
+                    found
+                )))
+            }
+        };
+
+        let ring_count = columns.iter().take_while(|c| is_number(c)).count();
+        if ring_count != rotors.len() {
+            return Err(malformed(format!(
+                "expected {} ring settings, found {}",
+                rotors.len(),
+                ring_count
+            )));
+        }
+        let (rings, columns) = columns.split_at(ring_count);
+        let ring_settings = rings
+            .iter()
+            .map(|ring| match ring.parse::<usize>() {
+                Ok(ring @ 1..=ALPHABET_SIZE) => Ok(ring - 1),
+                _ => Err(malformed(format!("ring setting '{}' is not 01-26", ring))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (reflector, plugs) = columns
+            .split_last()
+            .ok_or_else(|| malformed("missing reflector".to_string()))?;
+        if let Some(plug) = plugs
+            .iter()
+            .find(|p| p.len() != 2 || !p.bytes().all(|b| b.is_ascii_alphabetic()))
+        {
+            return Err(malformed(format!(
+                "plugboard pair '{}' is not two letters",
+                plug
+            )));
+        }
+
+        Ok(Self::new(model)
+            .rotors(rotors)
+            .ring_settings(&ring_settings)
+            .reflector(reflector)
+            .plugboard(&plugs.join(" ")))
+    }
+
+    /// Reads a configuration from the variables described in [`Machine::from_env`], looking
+    /// each up with `var` so it can be tested without touching the real environment.
+    pub(crate) fn from_vars(
+        var: impl Fn(&str) -> Result<String, VarError>,
+    ) -> Result<Self, InvalidArgsError> {
+        let var = |name: &str| match var(name) {
+            Ok(value) => Ok(Some(value)),
+            Err(VarError::NotPresent) => Ok(None),
+            Err(VarError::NotUnicode(_)) => Err(InvalidArgsError::InvalidKey(format!(
+                "{} is not valid unicode",
+                name
+            ))),
+        };
+
+        let rotors = var("ENIGMA_ROTORS")?;
+        let rotors: Option<Vec<&str>> = rotors
+            .as_deref()
+            .map(|rotors| rotors.split(',').map(str::trim).collect());
+        let mut config = match &rotors {
+            Some(rotors) if rotors.len() == 4 => Self::new(EnigmaModel::M4).rotors(rotors),
+            Some(rotors) => Self::new(EnigmaModel::M3).rotors(rotors),
+            None => Self::new(EnigmaModel::M3),
+        };
+        if let Some(rings) = var("ENIGMA_RINGS")? {
+            config = config.ring_settings(&parse_key_numbers(&rings, "ring setting")?);
+        }
+        if let Some(positions) = var("ENIGMA_POSITIONS")? {
+            config = config.positions(&parse_key_numbers(&positions, "position")?);
+        }
+        if let Some(reflector) = var("ENIGMA_REFLECTOR")? {
+            config = config.reflector(reflector.trim());
+        }
+        if let Some(plugboard) = var("ENIGMA_PLUGBOARD")? {
+            config = config.plugboard(&plugboard);
+        }
+        Ok(config)
+    }
+
+    /// Sets the names of the rotors, as accepted by [`Rotor::from_name`].
+    pub fn rotors(mut self, names: &[&str]) -> Self {
+        self.rotors = names.iter().map(|n| n.to_string()).collect();
+        self
+    }
+
+    /// Sets the rotors like [`MachineConfig::rotors`], from [`RotorName`]s.
+    pub fn rotor_names(mut self, names: &[RotorName]) -> Self {
+        self.rotors = names.iter().map(|n| n.to_string()).collect();
+        self
+    }
+
+    /// Sets the ring settings of the rotors.
+    pub fn ring_settings(mut self, ring_settings: &[usize]) -> Self {
+        self.ring_settings = ring_settings.to_vec();
+        self
+    }
+
+    /// Sets the initial positions of the rotors.
+    pub fn positions(mut self, positions: &[usize]) -> Self {
+        self.positions = positions.to_vec();
+        self
+    }
+
+    /// Sets the name of the reflector, as accepted by [`Reflector::from_name`].
+    pub fn reflector(mut self, name: &str) -> Self {
+        self.reflector = name.to_string();
+        self
+    }
+
+    /// Sets the position the reflector is turned to, reduced modulo
+    /// [`ALPHABET_SIZE`](crate::ALPHABET_SIZE). Defaults to 0.
+    ///
+    /// Only the [`EnigmaModel::G`]'s reflector could be turned, but any reflector can be given an
+    /// offset here to study a hypothetical turnable one. It's applied on the way in and undone on
+    /// the way out, so the reflector still pairs up the letters. It stays fixed unless the model
+    /// steps its reflector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::{ClockInt, MachineConfig};
+    /// let mut machine = MachineConfig::default().reflector_position(3).build().unwrap();
+    /// let encrypted = machine.encrypt("HELLOXWORLD");
+    /// assert_eq!(machine.reflector_position(), ClockInt::new(3));
+    ///
+    /// let mut machine = MachineConfig::default().reflector_position(3).build().unwrap();
+    /// assert_eq!(machine.encrypt(&encrypted), "HELLOXWORLD");
+    /// ```
+    pub fn reflector_position(mut self, position: usize) -> Self {
+        self.reflector_position = position;
+        self
+    }
+
+    /// Sets the plugboard connections, as accepted by [`Plugboard::new`]. By default there are
+    /// none, giving [`Plugboard::identity`].
+    pub fn plugboard(mut self, connections: &str) -> Self {
+        self.plugboard = connections.to_string();
+        self
+    }
+
+    /// Overrides the notches of the rotors, given as positions numbered from A = 0 like
+    /// [`Rotor::new`]'s `notch_position`. A rotor may have any number of notches, including none.
+    pub fn notches(mut self, notches: &[Vec<usize>]) -> Self {
+        self.notches = Some(notches.to_vec());
+        self
+    }
+
+    /// Sets whether the middle rotor double steps. Defaults to [`DoubleStepMode::Historical`].
+    pub fn double_step(mut self, mode: DoubleStepMode) -> Self {
+        self.double_step = mode;
+        self
+    }
+
+    /// Checks that every ring setting and position is less than [`ALPHABET_SIZE`], rather than
+    /// letting [`MachineConfig::build`] wrap them around, giving an
+    /// [`InvalidArgsError::SettingOutOfRange`] for the first that isn't.
+    pub fn check_ranges(&self) -> Result<(), InvalidArgsError> {
+        for (setting, values) in [
+            ("ring setting", &self.ring_settings),
+            ("position", &self.positions),
+        ] {
+            if let Some(&value) = values.iter().find(|&&v| v >= ALPHABET_SIZE) {
+                return Err(InvalidArgsError::SettingOutOfRange { setting, value });
+            }
+        }
+        Ok(())
+    }
+
+    /// Formats the rotors, ring settings, positions, reflector and plugboard as a key string that
+    /// [`FromStr`] reads back, with the ring settings and positions as letters if `letters` is set
+    /// or as numbers from 0 otherwise. They're reduced modulo [`ALPHABET_SIZE`] first. The other
+    /// settings, such as the notches, aren't part of a key string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::MachineConfig;
+    /// let config: MachineConfig = "I,II,III/1,1,27/M,C,K/B/AB CD".parse().unwrap();
+    /// assert_eq!(config.key_string(false), "I,II,III/1,1,1/12,2,10/B/AB CD");
+    /// assert_eq!(config.key_string(true), "I,II,III/B,B,B/M,C,K/B/AB CD");
+    /// ```
+    pub fn key_string(&self, letters: bool) -> String {
+        let format_values = |values: &[usize]| {
+            values
+                .iter()
+                .map(|&v| {
+                    let v = v % ALPHABET_SIZE;
+                    if letters {
+                        wire_to_char(v as u8).to_string()
+                    } else {
+                        v.to_string()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        let mut key = format!(
+            "{}/{}/{}/{}",
+            self.rotors.join(","),
+            format_values(&self.ring_settings),
+            format_values(&self.positions),
+            self.reflector
+        );
+        if !self.plugboard.is_empty() {
+            key.push('/');
+            key.push_str(&self.plugboard);
+        }
+        key
+    }
+
+    /// Builds the [`Machine`].
+    ///
+    /// Returns an error if the number of rotors, ring settings or positions doesn't match the
+    /// model, if a name is unknown or names a rotor or reflector the model doesn't take, if the
+    /// stepping rotors aren't all from the same [`RotorFamily`], or if the plugboard connections
+    /// are invalid.
+    pub fn build(&self) -> Result<Machine, InvalidArgsError> {
+        let count = self.model.rotor_count();
+        for found in [
+            self.rotors.len(),
+            self.ring_settings.len(),
+            self.positions.len(),
+            self.notches.as_ref().map_or(count, Vec::len),
+        ] {
+            if found != count {
+                return Err(InvalidArgsError::WrongRotorCount {
+                    expected: count,
+                    found,
+                });
+            }
+        }
+
+        let rotor_names = self
+            .rotors
+            .iter()
+            .map(|name| RotorName::try_from(name.as_str()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let reflector = Reflector::try_from_name(&self.reflector)?;
+        if let Some(e) = model_errors(self.model, &rotor_names, Some(&self.reflector))
+            .into_iter()
+            .next()
+        {
+            return Err(e);
+        }
+
+        let mut rotors = Vec::with_capacity(count);
+        for (i, &rotor_name) in rotor_names.iter().enumerate() {
+            let mut rotor = Rotor::named(rotor_name, self.ring_settings[i], self.positions[i]);
+            if let Some(notches) = &self.notches {
+                rotor = Rotor::new(
+                    rotor.wiring_encoding(),
+                    self.ring_settings[i],
+                    self.positions[i],
+                    notches[i].clone(),
+                );
+            }
+            rotors.push(rotor);
+        }
+
+        let plugboard = match self.plugboard.as_str() {
+            "" => Plugboard::identity(),
+            connections => Plugboard::new(connections)?,
+        };
+
+        let right = rotors.pop().expect("model has at least 3 rotors");
+        let middle = rotors.pop().expect("model has at least 3 rotors");
+        let left = rotors.pop().expect("model has at least 3 rotors");
+        let mut machine = Machine::from_model_components(
+            self.model,
+            self.double_step,
+            rotors.pop(),
+            (left, middle, right),
+            reflector,
+            plugboard,
+        );
+        machine.set_reflector_position(self.reflector_position);
+        Ok(machine)
+    }
+}
+
+/// Every reason the `rotors`, from left to right, and the `reflector`, if it's a known one, can't
+/// be fitted together in `model`: a rotor or reflector the model doesn't take, or stepping rotors
+/// from more than one [`RotorFamily`]. Shared by [`MachineConfig::build`] and
+/// [`Machine::validate_config`], so the two agree.
+pub(crate) fn model_errors(
+    model: EnigmaModel,
+    rotors: &[RotorName],
+    reflector: Option<&str>,
+) -> Vec<InvalidArgsError> {
+    let mut errors = Vec::new();
+    let mut family = None;
+    for (i, &rotor) in rotors.iter().enumerate() {
+        let fits = if model == EnigmaModel::M4 && i == 0 {
+            rotor.family() == RotorFamily::Greek
+        } else {
+            model.rotor_families().contains(&rotor.family())
+        };
+        if !fits {
+            errors.push(InvalidArgsError::NotInModel(rotor.as_str().to_string()));
+        } else if rotor.family() != RotorFamily::Greek
+            && *family.get_or_insert(rotor.family()) != rotor.family()
+        {
+            errors.push(InvalidArgsError::MixedRotorFamilies(
+                rotor.as_str().to_string(),
+            ));
+        }
+    }
+
+    if let Some(reflector) = reflector.filter(|r| !model.reflectors().contains(r)) {
+        errors.push(InvalidArgsError::NotInModel(reflector.to_string()));
+    }
+    errors
+}
+
+/// Encrypts `plaintext` with a fresh machine built from each of `configs`, e.g. to generate test
+/// vectors for checking against another implementation. The ciphertexts are in the same order as
+/// the configurations.
+///
+/// Returns the first error if a configuration can't be built.
+///
+/// # Examples
+///
+/// ```
+/// use enigma::{encrypt_over_keys, MachineConfig};
+/// let configs = (0..100).map(MachineConfig::seeded);
+/// let ciphertexts = encrypt_over_keys(configs, "HELLOXWORLD").unwrap();
+/// assert_eq!(ciphertexts.len(), 100);
+/// ```
+pub fn encrypt_over_keys(
+    configs: impl Iterator<Item = MachineConfig>,
+    plaintext: &str,
+) -> Result<Vec<String>, InvalidArgsError> {
+    configs
+        .map(|config| Ok(config.build()?.encrypt(plaintext)))
+        .collect()
+}
+
+/// Parses a key string of rotor names, ring settings, positions, reflector and optionally
+/// plugboard connections, separated by `/` or `:`, e.g. `"I,II,III/A,A,A/M,C,K/B/AB CD"`. Ring
+/// settings and positions may be given as letters or numbers from 0. Four rotors select the
+/// [`EnigmaModel::M4`], otherwise the [`EnigmaModel::M3`] is used.
+impl FromStr for MachineConfig {
+    type Err = InvalidArgsError;
+
+    fn from_str(key: &str) -> Result<Self, Self::Err> {
+        let separator = if key.contains('/') { '/' } else { ':' };
+        let segments: Vec<&str> = key.split(separator).collect();
+        if segments.len() != 4 && segments.len() != 5 {
+            return Err(InvalidArgsError::InvalidKey(format!(
+                "expected 4 or 5 segments (rotors{0}rings{0}positions{0}reflector[{0}plugboard]), \
+                 found {1}",
+                separator,
+                segments.len()
+            )));
+        }
+
+        let rotors: Vec<&str> = segments[0].split(',').map(str::trim).collect();
+        let model = if rotors.len() == 4 {
+            EnigmaModel::M4
+        } else {
+            EnigmaModel::M3
+        };
+        Ok(Self::new(model)
+            .rotors(&rotors)
+            .ring_settings(&parse_key_numbers(segments[1], "ring setting")?)
+            .positions(&parse_key_numbers(segments[2], "position")?)
+            .reflector(segments[3].trim())
+            .plugboard(segments.get(4).unwrap_or(&"")))
+    }
+}
+
+/// Parses comma-separated numbers or letters, where `A` is 0. They must be all letters or all
+/// numbers.
+fn parse_key_numbers(segment: &str, what: &str) -> Result<Vec<usize>, InvalidArgsError> {
+    let values = segment
+        .split(',')
+        .map(|s| {
+            let s = s.trim();
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_alphabetic() => {
+                    Ok((ClockInt::try_from(c)?.value(), true))
+                }
+                _ => s.parse().map(|n| (n, false)).map_err(|_| {
+                    InvalidArgsError::InvalidKey(format!(
+                        "{} '{}' is neither a number nor a letter",
+                        what, s
+                    ))
+                }),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if values.iter().any(|&(_, letter)| letter) && !values.iter().all(|&(_, letter)| letter) {
+        return Err(InvalidArgsError::InvalidKey(format!(
+            "{}s must be all letters or all numbers, found '{}'",
+            what,
+            segment.trim()
+        )));
+    }
+    Ok(values.into_iter().map(|(n, _)| n).collect())
+}
+
+/// The SplitMix64 pseudorandom number generator, used by [`MachineConfig::seeded`].
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`. The modulo bias is negligible for the small `n` used here.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// Fisher-Yates shuffle.
+    fn shuffle<T>(&mut self, values: &mut [T]) {
+        for i in (1..values.len()).rev() {
+            values.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+impl Default for MachineConfig {
+    /// The configuration of [`MachineConfig::new`] for the [`EnigmaModel::M3`].
+    fn default() -> Self {
+        Self::new(EnigmaModel::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::HashMap;
+
+    proptest! {
+        #[test]
+        fn prop_from_str_never_panics(key in "[A-Z0-9a-z,:/ -]{0,60}|\\PC*") {
+            if let Ok(config) = key.parse::<MachineConfig>() {
+                let _ = config.build();
+            }
+        }
+
+        #[test]
+        fn prop_from_keysheet_line_never_panics(line in "[A-Z0-9 -]{0,60}|\\PC*") {
+            if let Ok(config) = MachineConfig::from_keysheet_line(&line) {
+                let _ = config.build();
+            }
+        }
+
+        #[test]
+        fn prop_seeded_round_trip(seed in any::<u64>(), message in "[A-Z]{1,200}") {
+            let mut machine = MachineConfig::seeded(seed).build().unwrap();
+            let encrypted = machine.encrypt(&message);
+            let mut machine = MachineConfig::seeded(seed).build().unwrap();
+            prop_assert_eq!(machine.encrypt(&encrypted), message);
+        }
+    }
+
+    #[test]
+    fn test_default() {
+        assert_eq!(
+            MachineConfig::default().build(),
+            Ok(Machine::new(
+                ("I", "II", "III"),
+                (0, 0, 0),
+                (0, 0, 0),
+                "B",
+                ""
+            ))
+        );
+        for model in [EnigmaModel::M3, EnigmaModel::M4, EnigmaModel::G] {
+            let machine = MachineConfig::new(model).build().unwrap();
+            assert_eq!(machine.model(), model);
+        }
+    }
+
+    #[test]
+    fn test_hash() {
+        use std::collections::HashSet;
+
+        let configs: HashSet<MachineConfig> = (0..50)
+            .map(|i| MachineConfig::seeded(i % 10))
+            .chain([
+                MachineConfig::default(),
+                MachineConfig::new(EnigmaModel::M3),
+            ])
+            .chain([
+                MachineConfig::new(EnigmaModel::G),
+                MachineConfig::new(EnigmaModel::G).double_step(DoubleStepMode::None),
+            ])
+            .collect();
+        assert_eq!(configs.len(), 10 + 1 + 2);
+    }
+
+    #[test]
+    fn test_reflector_position() {
+        // At 0 the output is exactly as before the offset could be set.
+        let message = "THEXQUICKXBROWNXFOXXJUMPSXOVERXTHEXLAZYXDOG".repeat(5);
+        let plugs = "AV BS CG DL FU HZ IN KM OW RX";
+        for reflector in ["B", "C"] {
+            let config = MachineConfig::default()
+                .rotors(&["II", "IV", "V"])
+                .ring_settings(&[1, 20, 11])
+                .positions(&[1, 11, 0])
+                .reflector(reflector)
+                .plugboard(plugs);
+            let mut machine =
+                Machine::new(("II", "IV", "V"), (1, 20, 11), (1, 11, 0), reflector, plugs);
+            let expected = machine.encrypt(&message);
+            assert_eq!(
+                config
+                    .clone()
+                    .reflector_position(0)
+                    .build()
+                    .unwrap()
+                    .encrypt(&message),
+                expected
+            );
+
+            let mut turned = config.reflector_position(1).build().unwrap();
+            let encrypted = turned.encrypt(&message);
+            assert_ne!(encrypted, expected);
+            // The offset stays put on the M3.
+            assert_eq!(turned.reflector_position(), ClockInt::new(1));
+            assert!(encrypted.chars().zip(message.chars()).all(|(e, m)| e != m));
+        }
+    }
+
+    #[test]
+    fn test_m4_matches_m3() {
+        // With the Greek rotor and ring at A, the thin reflectors and Greek rotors were designed
+        // to behave like the M3's reflectors B and C, so M4 messages could be read by M3s.
+        let message = "THEXQUICKXBROWNXFOXXJUMPSXOVERXTHEXLAZYXDOG".repeat(20);
+        for (greek, thin, reflector) in [("BETA", "B-THIN", "B"), ("GAMMA", "C-THIN", "C")] {
+            let mut m4 = MachineConfig::new(EnigmaModel::M4)
+                .rotors(&[greek, "II", "IV", "I"])
+                .ring_settings(&[0, 3, 7, 21])
+                .positions(&[0, 9, 13, 5])
+                .reflector(thin)
+                .plugboard("AT BL DF GJ HM NW OP QY RZ VX")
+                .build()
+                .unwrap();
+            let mut m3 = Machine::new(
+                ("II", "IV", "I"),
+                (3, 7, 21),
+                (9, 13, 5),
+                reflector,
+                "AT BL DF GJ HM NW OP QY RZ VX",
+            );
+            assert_eq!(m4.encrypt(&message), m3.encrypt(&message));
+        }
+    }
+
+    #[test]
+    fn test_notches() {
+        // Moving rotor III's notch from V to U makes the middle rotor step a letter earlier.
+        let mut machine = MachineConfig::default()
+            .positions(&[0, 0, 20])
+            .notches(&[vec![16], vec![4], vec![20]])
+            .build()
+            .unwrap();
+        machine.encrypt("A");
+        assert_eq!(machine.positions().1, ClockInt::new(1));
+
+        let mut machine = MachineConfig::default()
+            .positions(&[0, 0, 20])
+            .notches(&[vec![], vec![], vec![]])
+            .build()
+            .unwrap();
+        machine.encrypt(&"A".repeat(100));
+        assert_eq!(machine.positions().1, ClockInt::new(0));
+
+        assert_eq!(
+            MachineConfig::default().notches(&[vec![1]]).build(),
+            Err(InvalidArgsError::WrongRotorCount {
+                expected: 3,
+                found: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_double_step_mode() {
+        let config = MachineConfig::default().positions(&[0, 3, 20]);
+        let mut historical = config.clone().build().unwrap();
+        let mut odometer = config.double_step(DoubleStepMode::None).build().unwrap();
+
+        // The modes agree until the middle rotor reaches its notch at E, after the second letter.
+        assert_eq!(historical.encrypt("AA"), odometer.encrypt("AA"));
+        assert_eq!(historical.positions(), odometer.positions());
+
+        // Then the real machine steps the middle and left rotors at once, ADU -> ADV -> AEW -> BFX.
+        historical.encrypt("A");
+        odometer.encrypt("A");
+        let letters = |p: (ClockInt, ClockInt, ClockInt)| format!("{}{}{}", p.0, p.1, p.2);
+        assert_eq!(letters(historical.positions()), "BFX");
+        assert_eq!(letters(odometer.positions()), "AEX");
+        assert_ne!(historical.encrypt("AAAA"), odometer.encrypt("AAAA"));
+    }
+
+    #[test]
+    fn test_key_string() {
+        let config: MachineConfig = "I,II,III/1,1,27/A,B,C/B/AB CD".parse().unwrap();
+        assert_eq!(config.key_string(false), "I,II,III/1,1,1/0,1,2/B/AB CD");
+        assert_eq!(config.key_string(true), "I,II,III/B,B,B/A,B,C/B/AB CD");
+
+        // Either form can be read back, for any model.
+        for config in [
+            "IV,V,VI:A,M,Z:0,0,0:C".parse().unwrap(),
+            MachineConfig::new(EnigmaModel::M4).positions(&[1, 2, 3, 4]),
+            MachineConfig::seeded(3),
+        ] {
+            for letters in [false, true] {
+                let key = config.key_string(letters);
+                assert_eq!(
+                    key.parse::<MachineConfig>().unwrap().key_string(true),
+                    config.key_string(true)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_check_ranges() {
+        assert_eq!(
+            MachineConfig::default()
+                .positions(&[0, 12, 25])
+                .check_ranges(),
+            Ok(())
+        );
+        assert_eq!(
+            MachineConfig::default()
+                .positions(&[0, 26, 30])
+                .check_ranges(),
+            Err(InvalidArgsError::SettingOutOfRange {
+                setting: "position",
+                value: 26
+            })
+        );
+        assert_eq!(
+            MachineConfig::default()
+                .ring_settings(&[27, 0, 0])
+                .check_ranges(),
+            Err(InvalidArgsError::SettingOutOfRange {
+                setting: "ring setting",
+                value: 27
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        let expected = MachineConfig::default()
+            .ring_settings(&[0, 1, 25])
+            .positions(&[12, 2, 10])
+            .plugboard("AB CD");
+        assert_eq!("I,II,III/A,B,Z/M,C,K/B/AB CD".parse(), Ok(expected.clone()));
+        assert_eq!("I, II, III:0,1,25:12,2,10:B:AB CD".parse(), Ok(expected));
+        assert_eq!(
+            "BETA,I,II,III/A,A,A,A/A,A,A,A/B-THIN".parse(),
+            Ok(MachineConfig::new(EnigmaModel::M4))
+        );
+
+        assert!(matches!(
+            "I,II,III/A,A,A/A,A,A".parse::<MachineConfig>(),
+            Err(InvalidArgsError::InvalidKey(_))
+        ));
+        assert_eq!(
+            "I,II,III/1,B,3/A,A,A/B".parse::<MachineConfig>(),
+            Err(InvalidArgsError::InvalidKey(
+                "ring settings must be all letters or all numbers, found '1,B,3'".to_string()
+            ))
+        );
+        assert_eq!(
+            "I,II,III/A,A,A/12,c,10/B".parse::<MachineConfig>(),
+            Err(InvalidArgsError::InvalidKey(
+                "positions must be all letters or all numbers, found '12,c,10'".to_string()
+            ))
+        );
+        assert_eq!(
+            "I,II,III/A,AB,A/A,A,A/B".parse::<MachineConfig>(),
+            Err(InvalidArgsError::InvalidKey(
+                "ring setting 'AB' is neither a number nor a letter".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_from_keysheet_line() {
+        assert_eq!(
+            MachineConfig::from_keysheet_line(
+                " 31  I IV III   16 26 08  AD CN ET FL GI JV KZ PU QY WX  B\n"
+            ),
+            Ok(MachineConfig::default()
+                .rotors(&["I", "IV", "III"])
+                .ring_settings(&[15, 25, 7])
+                .plugboard("AD CN ET FL GI JV KZ PU QY WX"))
+        );
+        assert_eq!(
+            MachineConfig::from_keysheet_line("1 BETA II IV I 01 01 01 22 C-THIN"),
+            Ok(MachineConfig::new(EnigmaModel::M4)
+                .rotors(&["BETA", "II", "IV", "I"])
+                .ring_settings(&[0, 0, 0, 21])
+                .reflector("C-THIN"))
+        );
+
+        for (line, reason) in [
+            ("", "day '' is not a number 1-31"),
+            ("32 I II III 01 01 01 B", "day '32' is not a number 1-31"),
+            ("1 I II 01 01 B", "expected 3 or 4 rotors, found 2"),
+            ("1 I II III 01 01 B", "expected 3 ring settings, found 2"),
+            ("1 I II III 01 27 01 B", "ring setting '27' is not 01-26"),
+            ("1 I II III 01 01 01", "missing reflector"),
+            (
+                "1 I II III 01 01 01 ABC B",
+                "plugboard pair 'ABC' is not two letters",
+            ),
+        ] {
+            assert_eq!(
+                MachineConfig::from_keysheet_line(line),
+                Err(InvalidArgsError::InvalidKey(format!(
+                    "key sheet line '{}': {}",
+                    line, reason
+                ))),
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_vars() {
+        let from_vars = |vars: &[(&str, &str)]| {
+            let vars: HashMap<String, String> = vars
+                .iter()
+                .map(|&(name, value)| (name.to_string(), value.to_string()))
+                .collect();
+            MachineConfig::from_vars(|name| vars.get(name).cloned().ok_or(VarError::NotPresent))
+        };
+
+        assert_eq!(from_vars(&[]), Ok(MachineConfig::default()));
+        assert_eq!(
+            from_vars(&[
+                ("ENIGMA_ROTORS", "II, IV,V"),
+                ("ENIGMA_RINGS", "1,20,11"),
+                ("ENIGMA_POSITIONS", "B,L,A"),
+                ("ENIGMA_REFLECTOR", "C"),
+                ("ENIGMA_PLUGBOARD", "AV BS"),
+            ]),
+            Ok(MachineConfig::default()
+                .rotors(&["II", "IV", "V"])
+                .ring_settings(&[1, 20, 11])
+                .positions(&[1, 11, 0])
+                .reflector("C")
+                .plugboard("AV BS"))
+        );
+        assert_eq!(
+            from_vars(&[("ENIGMA_ROTORS", "GAMMA,I,II,III")]),
+            Ok(MachineConfig::new(EnigmaModel::M4).rotors(&["GAMMA", "I", "II", "III"]))
+        );
+
+        assert_eq!(
+            from_vars(&[("ENIGMA_POSITIONS", "A,?,A")]),
+            Err(InvalidArgsError::InvalidKey(
+                "position '?' is neither a number nor a letter".to_string()
+            ))
+        );
+        assert_eq!(
+            MachineConfig::from_vars(|_| Err(VarError::NotUnicode(Default::default()))),
+            Err(InvalidArgsError::InvalidKey(
+                "ENIGMA_ROTORS is not valid unicode".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_seeded() {
+        assert_eq!(MachineConfig::seeded(7), MachineConfig::seeded(7));
+        assert_ne!(MachineConfig::seeded(7), MachineConfig::seeded(8));
+
+        for seed in 0..100 {
+            let config = MachineConfig::seeded(seed);
+            assert_eq!(
+                Machine::validate_config(
+                    (&config.rotors[0], &config.rotors[1], &config.rotors[2]),
+                    (
+                        config.ring_settings[0],
+                        config.ring_settings[1],
+                        config.ring_settings[2]
+                    ),
+                    (
+                        config.positions[0],
+                        config.positions[1],
+                        config.positions[2]
+                    ),
+                    &config.reflector,
+                    &config.plugboard,
+                ),
+                Ok(())
+            );
+            assert_eq!(config.plugboard.split_whitespace().count(), 10);
+            assert!(config.build().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_encrypt_over_keys() {
+        let configs = [
+            MachineConfig::default(),
+            MachineConfig::seeded(1),
+            MachineConfig::new(EnigmaModel::M4),
+        ];
+        let ciphertexts = encrypt_over_keys(configs.iter().cloned(), "HELLOXWORLD").unwrap();
+        for (config, ciphertext) in configs.iter().zip(&ciphertexts) {
+            assert_eq!(config.build().unwrap().encrypt("HELLOXWORLD"), *ciphertext);
+        }
+
+        let invalid = MachineConfig::default().reflector("E");
+        assert_eq!(
+            encrypt_over_keys([configs[0].clone(), invalid].into_iter(), "HELLO"),
+            Err(InvalidArgsError::InvalidReflectorName("E".to_string()))
+        );
+        assert_eq!(encrypt_over_keys(std::iter::empty(), "HELLO"), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_splitmix64() {
+        // First outputs for seed 1234567 from the reference implementation.
+        let mut rng = SplitMix64(1234567);
+        assert_eq!(rng.next(), 6457827717110365317);
+        assert_eq!(rng.next(), 3203168211198807973);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(
+            MachineConfig::new(EnigmaModel::M4)
+                .rotors(&["I", "II", "III"])
+                .build(),
+            Err(InvalidArgsError::WrongRotorCount {
+                expected: 4,
+                found: 3
+            })
+        );
+        assert_eq!(
+            MachineConfig::new(EnigmaModel::M3)
+                .rotors(&["BETA", "I", "II", "III"])
+                .build(),
+            Err(InvalidArgsError::WrongRotorCount {
+                expected: 3,
+                found: 4
+            })
+        );
+        assert_eq!(
+            MachineConfig::default().positions(&[0, 0]).build(),
+            Err(InvalidArgsError::WrongRotorCount {
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            MachineConfig::default()
+                .rotors(&["I", "G-II", "III"])
+                .build(),
+            Err(InvalidArgsError::NotInModel("G-II".to_string()))
+        );
+        assert_eq!(
+            MachineConfig::default()
+                .rotors(&["I", "NORWAY-II", "III"])
+                .build(),
+            Err(InvalidArgsError::MixedRotorFamilies(
+                "NORWAY-II".to_string()
+            ))
+        );
+        assert!(MachineConfig::default()
+            .rotors(&["NORWAY-I", "NORWAY-II", "NORWAY-III"])
+            .reflector("NORWAY")
+            .build()
+            .is_ok());
+        assert_eq!(
+            MachineConfig::new(EnigmaModel::M4)
+                .rotors(&["I", "BETA", "II", "III"])
+                .build(),
+            Err(InvalidArgsError::NotInModel("I".to_string()))
+        );
+        assert_eq!(
+            MachineConfig::new(EnigmaModel::M4).reflector("B").build(),
+            Err(InvalidArgsError::NotInModel("B".to_string()))
+        );
+        assert_eq!(
+            MachineConfig::default().reflector("E").build(),
+            Err(InvalidArgsError::InvalidReflectorName("E".to_string()))
+        );
+        assert_eq!(
+            MachineConfig::default().plugboard("AB AC").build(),
+            Err(InvalidArgsError::DuplicatePlug('A'))
+        );
+    }
+}
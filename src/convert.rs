@@ -0,0 +1,77 @@
+//! Conversions between letters and wire numbers.
+//!
+//! The components work on wire numbers rather than letters, see
+//! [`Component`](crate::Component). Wires are numbered from 0 for `A` to 25 for `Z`, in
+//! alphabetical order, and the conversions accept letters in either case but always give uppercase
+//! letters back.
+use crate::InvalidArgsError;
+
+/// Converts an ASCII letter, in either case, to its wire number, where `A` is 0, returning an error
+/// for anything else.
+///
+/// # Examples
+///
+/// ```
+/// use enigma::convert::try_char_to_wire;
+/// use enigma::InvalidArgsError;
+/// assert_eq!(try_char_to_wire('C'), Ok(2));
+/// assert_eq!(try_char_to_wire('z'), Ok(25));
+/// assert_eq!(try_char_to_wire('!'), Err(InvalidArgsError::NotALetter('!')));
+/// ```
+pub fn try_char_to_wire(c: char) -> Result<u8, InvalidArgsError> {
+    if c.is_ascii_alphabetic() {
+        Ok(char_to_wire(c))
+    } else {
+        Err(InvalidArgsError::NotALetter(c))
+    }
+}
+
+/// Converts an ASCII letter, in either case, to its wire number, where `A` is 0.
+///
+/// `c` must be an ASCII letter. This is only checked in debug builds, so use
+/// [`try_char_to_wire`] for input that hasn't been checked already.
+///
+/// # Examples
+///
+/// ```
+/// use enigma::convert::char_to_wire;
+/// assert_eq!(char_to_wire('A'), 0);
+/// assert_eq!(char_to_wire('q'), 16);
+/// ```
+pub fn char_to_wire(c: char) -> u8 {
+    debug_assert!(c.is_ascii_alphabetic());
+    (c.to_ascii_uppercase() as u8) - b'A'
+}
+
+/// Converts a wire number to its uppercase letter, where 0 is `A`.
+///
+/// `w` must be less than [`ALPHABET_SIZE`](crate::ALPHABET_SIZE). This is only checked in debug
+/// builds.
+///
+/// # Examples
+///
+/// ```
+/// use enigma::convert::{char_to_wire, wire_to_char};
+/// assert_eq!(wire_to_char(25), 'Z');
+/// assert_eq!(wire_to_char(char_to_wire('m')), 'M');
+/// ```
+pub fn wire_to_char(w: u8) -> char {
+    debug_assert!((w as usize) < crate::ALPHABET_SIZE);
+    (w + b'A') as char
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_char_to_wire() {
+        for (w, c) in ('A'..='Z').enumerate() {
+            assert_eq!(try_char_to_wire(c), Ok(w as u8));
+            assert_eq!(try_char_to_wire(c.to_ascii_lowercase()), Ok(w as u8));
+        }
+        for c in ['@', '[', '`', '{', '1', ' ', 'é', 'Ω'] {
+            assert_eq!(try_char_to_wire(c), Err(InvalidArgsError::NotALetter(c)));
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! Ciphertext-only key search, scoring trial decryptions with [`chi_squared_english`].
+//!
+//! The search assumes no plugboard and recovers the key in two stages, as in Gillogly's attack.
+//! [`best_rotor_order`] tries every rotor order and starting position with the ring settings at
+//! `A`, using [`best_positions`] for each order, then [`best_ring_settings`] refines the ring
+//! settings of the right and middle rotors. The left rotor's ring setting never matters, as it
+//! only shifts the left rotor like its position.
+//!
+//! Both stages take a while for a full search, so each has a `_with_progress` variant taking a
+//! callback that's given the number of trials done so far and the total.
+use crate::analysis::chi_squared_english;
+use crate::{InvalidArgsError, Machine, MachineConfig, ALPHABET_SIZE};
+
+/// A key found by the search, with the score of its decryption. Lower scores are better.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidate {
+    /// The settings found. Build a machine from them to decrypt the message.
+    pub config: MachineConfig,
+    /// The [`chi_squared_english`] score of the decryption.
+    pub score: f64,
+}
+
+/// Finds the order of three of the `rotors` and their starting positions that give the most
+/// English-like decryption of `ciphertext`, with the reflector `reflector` and the ring settings
+/// at `A`. See [`best_rotor_order_with_progress`].
+pub fn best_rotor_order(
+    ciphertext: &str,
+    rotors: &[&str],
+    reflector: &str,
+) -> Result<Candidate, InvalidArgsError> {
+    best_rotor_order_with_progress(ciphertext, rotors, reflector, |_, _| {})
+}
+
+/// Does the same as [`best_rotor_order`], calling `progress` with the number of trials done and
+/// the total as the search goes on. Every order of three different rotors from `rotors` is tried,
+/// in all 17,576 starting positions.
+///
+/// Returns an error if fewer than three `rotors` are given, or if a name isn't valid.
+pub fn best_rotor_order_with_progress(
+    ciphertext: &str,
+    rotors: &[&str],
+    reflector: &str,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<Candidate, InvalidArgsError> {
+    if rotors.len() < 3 {
+        return Err(InvalidArgsError::WrongRotorCount {
+            expected: 3,
+            found: rotors.len(),
+        });
+    }
+
+    let mut orders = Vec::new();
+    for (i, &left) in rotors.iter().enumerate() {
+        for (j, &middle) in rotors.iter().enumerate() {
+            for (k, &right) in rotors.iter().enumerate() {
+                if i != j && i != k && j != k {
+                    orders.push([left, middle, right]);
+                }
+            }
+        }
+    }
+
+    let positions_per_order = ALPHABET_SIZE.pow(3);
+    let total = orders.len() * positions_per_order;
+    let mut best: Option<Candidate> = None;
+    for (n, order) in orders.iter().enumerate() {
+        let candidate = best_positions_with_progress(ciphertext, *order, reflector, |done, _| {
+            progress(n * positions_per_order + done, total)
+        })?;
+        if best
+            .as_ref()
+            .is_none_or(|best| candidate.score < best.score)
+        {
+            best = Some(candidate);
+        }
+    }
+
+    Ok(best.expect("at least one rotor order is tried"))
+}
+
+/// Finds the starting positions of the `rotors`, in the order given, that give the most
+/// English-like decryption of `ciphertext`, with the reflector `reflector` and the ring settings
+/// at `A`. See [`best_positions_with_progress`].
+pub fn best_positions(
+    ciphertext: &str,
+    rotors: [&str; 3],
+    reflector: &str,
+) -> Result<Candidate, InvalidArgsError> {
+    best_positions_with_progress(ciphertext, rotors, reflector, |_, _| {})
+}
+
+/// Does the same as [`best_positions`], calling `progress` with the number of trials done and the
+/// total as the search goes on. All 17,576 starting positions are tried.
+///
+/// Returns an error if a name isn't valid.
+pub fn best_positions_with_progress(
+    ciphertext: &str,
+    rotors: [&str; 3],
+    reflector: &str,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<Candidate, InvalidArgsError> {
+    let config = MachineConfig::default()
+        .rotors(&rotors)
+        .reflector(reflector);
+    let mut machine = config.build()?;
+
+    let total = ALPHABET_SIZE.pow(3);
+    let mut best: Option<Candidate> = None;
+    for left in 0..ALPHABET_SIZE {
+        for position in 0..ALPHABET_SIZE.pow(2) {
+            let positions = [left, position / ALPHABET_SIZE, position % ALPHABET_SIZE];
+            let score = score(&mut machine, ciphertext, positions);
+            if best.as_ref().is_none_or(|best| score < best.score) {
+                best = Some(Candidate {
+                    config: config.clone().positions(&positions),
+                    score,
+                });
+            }
+        }
+        progress((left + 1) * ALPHABET_SIZE.pow(2), total);
+    }
+
+    Ok(best.expect("at least one position is tried"))
+}
+
+/// Finds the ring settings of the right and middle rotors that give the most English-like
+/// decryption of `ciphertext`, starting from a `candidate` found by [`best_rotor_order`]. See
+/// [`best_ring_settings_with_progress`].
+pub fn best_ring_settings(
+    ciphertext: &str,
+    candidate: &Candidate,
+) -> Result<Candidate, InvalidArgsError> {
+    best_ring_settings_with_progress(ciphertext, candidate, |_, _| {})
+}
+
+/// Does the same as [`best_ring_settings`], calling `progress` with the number of trials done and
+/// the total as the search goes on.
+///
+/// All 676 ring settings of the right and middle rotors are tried. Each rotor's position is moved
+/// along with its ring setting, so the wiring stays where the first stage found it and only the
+/// points where the rotors turn over change.
+///
+/// Returns an error if the candidate's settings aren't valid.
+pub fn best_ring_settings_with_progress(
+    ciphertext: &str,
+    candidate: &Candidate,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<Candidate, InvalidArgsError> {
+    let mut machine = candidate.config.build()?;
+    let positions = machine.positions();
+    let rings = machine.ring_settings();
+
+    let total = ALPHABET_SIZE.pow(2);
+    let mut best = candidate.clone();
+    for (n, (middle, right)) in (0..ALPHABET_SIZE)
+        .flat_map(|middle| (0..ALPHABET_SIZE).map(move |right| (middle, right)))
+        .enumerate()
+    {
+        let ring_settings = [rings.0.value(), middle, right];
+        let positions = [
+            positions.0.value(),
+            (positions.1.value() + middle + ALPHABET_SIZE - rings.1.value()) % ALPHABET_SIZE,
+            (positions.2.value() + right + ALPHABET_SIZE - rings.2.value()) % ALPHABET_SIZE,
+        ];
+        let config = candidate
+            .config
+            .clone()
+            .ring_settings(&ring_settings)
+            .positions(&positions);
+        machine = config.build()?;
+
+        let score = chi_squared_english(&machine.encrypt(ciphertext));
+        if score < best.score {
+            best = Candidate { config, score };
+        }
+        progress(n + 1, total);
+    }
+
+    Ok(best)
+}
+
+/// Scores the decryption of `ciphertext` with `machine` from `positions`.
+fn score(machine: &mut Machine, ciphertext: &str, positions: [usize; 3]) -> f64 {
+    machine.set_positions((positions[0], positions[1], positions[2]));
+    chi_squared_english(&machine.encrypt(ciphertext))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAINTEXT: &str =
+        "THEENIGMAMACHINEISACIPHERDEVICEDEVELOPEDANDUSEDINTHEEARLYTOMIDTWENTIETH\
+                             CENTURYTOPROTECTCOMMERCIALDIPLOMATICANDMILITARYCOMMUNICATION";
+
+    fn encrypt(rotors: [&str; 3], rings: [usize; 3], positions: [usize; 3]) -> String {
+        MachineConfig::default()
+            .rotors(&rotors)
+            .ring_settings(&rings)
+            .positions(&positions)
+            .build()
+            .unwrap()
+            .encrypt(PLAINTEXT)
+    }
+
+    #[test]
+    fn test_best_positions() {
+        let ciphertext = encrypt(["III", "I", "II"], [0, 0, 0], [7, 19, 2]);
+
+        let mut calls = Vec::new();
+        let candidate =
+            best_positions_with_progress(&ciphertext, ["III", "I", "II"], "B", |done, total| {
+                calls.push((done, total))
+            })
+            .unwrap();
+        assert_eq!(
+            candidate.config,
+            MachineConfig::default()
+                .rotors(&["III", "I", "II"])
+                .positions(&[7, 19, 2])
+        );
+        assert_eq!(calls.len(), 26);
+        assert_eq!(calls.last(), Some(&(17576, 17576)));
+        assert!(calls.windows(2).all(|w| w[0].0 < w[1].0));
+
+        assert!(best_positions(&ciphertext, ["I", "II", "IX"], "B").is_err());
+    }
+
+    #[test]
+    #[ignore = "searches all 6 × 17,576 keys, which is slow in debug builds"]
+    fn test_best_rotor_order() {
+        let ciphertext = encrypt(["III", "I", "II"], [0, 0, 0], [7, 19, 2]);
+
+        let mut calls = Vec::new();
+        let candidate =
+            best_rotor_order_with_progress(&ciphertext, &["I", "II", "III"], "B", |done, total| {
+                calls.push((done, total))
+            })
+            .unwrap();
+        assert_eq!(
+            candidate.config,
+            MachineConfig::default()
+                .rotors(&["III", "I", "II"])
+                .positions(&[7, 19, 2])
+        );
+        assert_eq!(calls.len(), 6 * 26);
+        assert_eq!(calls.last(), Some(&(6 * 17576, 6 * 17576)));
+        assert!(calls.windows(2).all(|w| w[0].0 < w[1].0));
+
+        assert!(best_rotor_order(&ciphertext, &["I", "II", "IX"], "B").is_err());
+    }
+
+    #[test]
+    fn test_best_rotor_order_too_few_rotors() {
+        assert_eq!(
+            best_rotor_order(PLAINTEXT, &["I", "II"], "B"),
+            Err(InvalidArgsError::WrongRotorCount {
+                expected: 3,
+                found: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_best_ring_settings() {
+        let ciphertext = encrypt(["II", "IV", "V"], [0, 5, 11], [3, 8, 20]);
+        // What the first stage would find: the same wiring offsets with the rings at A.
+        let first_stage = Candidate {
+            config: MachineConfig::default()
+                .rotors(&["II", "IV", "V"])
+                .positions(&[3, 3, 9]),
+            score: f64::INFINITY,
+        };
+
+        let mut calls = 0;
+        let candidate =
+            best_ring_settings_with_progress(&ciphertext, &first_stage, |_, _| calls += 1).unwrap();
+        // Chi-squared can't always tell the key from one that's wrong for a few letters after a
+        // turnover, but the search must find a decryption at least as good as the real key's.
+        let real_key = first_stage
+            .config
+            .clone()
+            .ring_settings(&[0, 5, 11])
+            .positions(&[3, 8, 20]);
+        let real_score = chi_squared_english(&real_key.build().unwrap().encrypt(&ciphertext));
+        assert!(candidate.score <= real_score);
+        let decrypted = candidate.config.build().unwrap().encrypt(&ciphertext);
+        assert!(crate::analysis::diff(PLAINTEXT, &decrypted).len() < 20);
+        assert_eq!(calls, 676);
+    }
+}
@@ -0,0 +1,92 @@
+use std::fmt::Display;
+
+/// Error returned when the machine or one of its components is given invalid settings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InvalidArgsError {
+    /// A wiring encoding isn't a permutation of the alphabet.
+    InvalidWiring(String),
+    /// There is no rotor with the given name.
+    InvalidRotorName(String),
+    /// There is no reflector with the given name.
+    InvalidReflectorName(String),
+    /// A group of plugboard connections has odd length or contains something other than letters.
+    InvalidPlugboard(String),
+    /// A letter is connected more than once on the plugboard.
+    DuplicatePlug(char),
+    /// The number of rotors, ring settings or positions doesn't match the model.
+    WrongRotorCount {
+        /// How many the model takes.
+        expected: usize,
+        /// How many were given.
+        found: usize,
+    },
+    /// A rotor or reflector isn't one the model could be fitted with.
+    NotInModel(String),
+    /// A ring setting or position isn't less than [`ALPHABET_SIZE`](crate::ALPHABET_SIZE).
+    SettingOutOfRange {
+        /// Which kind of setting, e.g. `"ring setting"`.
+        setting: &'static str,
+        /// The value given.
+        value: usize,
+    },
+    /// The same rotor was used in more than one slot, which the real machine can't do.
+    RepeatedRotor(String),
+    /// A rotor is from a different [`RotorFamily`](crate::RotorFamily) than the other stepping
+    /// rotors, so it was never used alongside them.
+    MixedRotorFamilies(String),
+    /// A key string isn't in the expected format.
+    InvalidKey(String),
+    /// Reflector pairs don't pair up every letter with exactly one other.
+    InvalidReflectorPairs(String),
+    /// A character that should be a letter isn't an ASCII letter.
+    NotALetter(char),
+    /// A name was empty or only whitespace. Holds what should have been named, e.g. `"rotor"`.
+    Empty(&'static str),
+}
+
+impl Display for InvalidArgsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidWiring(encoding) => write!(
+                f,
+                "invalid wiring '{}': must contain each letter A-Z exactly once",
+                encoding
+            ),
+            Self::InvalidRotorName(name) => write!(f, "unknown rotor name '{}'", name),
+            Self::InvalidReflectorName(name) => write!(f, "unknown reflector name '{}'", name),
+            Self::InvalidPlugboard(group) => write!(
+                f,
+                "invalid plugboard connections '{}': must be pairs of letters",
+                group
+            ),
+            Self::DuplicatePlug(c) => write!(f, "letter '{}' is connected more than once", c),
+            Self::WrongRotorCount { expected, found } => {
+                write!(
+                    f,
+                    "expected settings for {} rotors, found {}",
+                    expected, found
+                )
+            }
+            Self::NotInModel(name) => write!(f, "'{}' isn't available on this model", name),
+            Self::SettingOutOfRange { setting, value } => write!(
+                f,
+                "{} {} is out of range, must be less than {}",
+                setting,
+                value,
+                crate::ALPHABET_SIZE
+            ),
+            Self::RepeatedRotor(name) => write!(f, "rotor '{}' is used more than once", name),
+            Self::MixedRotorFamilies(name) => write!(
+                f,
+                "rotor '{}' is from a different family than the other rotors",
+                name
+            ),
+            Self::InvalidKey(reason) => write!(f, "invalid key: {}", reason),
+            Self::InvalidReflectorPairs(reason) => write!(f, "invalid reflector pairs: {}", reason),
+            Self::NotALetter(c) => write!(f, "'{}' is not an ASCII letter", c),
+            Self::Empty(what) => write!(f, "no {} specified", what),
+        }
+    }
+}
+
+impl std::error::Error for InvalidArgsError {}
@@ -0,0 +1,223 @@
+//! C interface to the [`Machine`], enabled with the `ffi` feature.
+//!
+//! The declarations are in `include/enigma.h`. To build a shared or static library run e.g.
+//!
+//! ```text
+//! cargo rustc --lib --release --features ffi --crate-type cdylib
+//! cargo rustc --lib --release --features ffi --crate-type staticlib
+//! ```
+//!
+//! Machines are passed around as opaque pointers created by [`enigma_new`] and released with
+//! [`enigma_free`]. Strings are null-terminated and UTF-8. Functions that fail return a null
+//! pointer, after which [`enigma_last_error`] describes what went wrong.
+
+use crate::Machine;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: impl Into<String>) {
+    // The messages never contain interior nul bytes, but don't panic across the FFI boundary if
+    // one ever does.
+    let message = CString::new(message.into()).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// Reads a null-terminated UTF-8 string, recording an error naming `what` if it's null or invalid.
+unsafe fn read_str<'a>(s: *const c_char, what: &str) -> Option<&'a str> {
+    if s.is_null() {
+        set_last_error(format!("{} is null", what));
+        return None;
+    }
+
+    match CStr::from_ptr(s).to_str() {
+        Ok(s) => Some(s),
+        Err(_) => {
+            set_last_error(format!("{} is not valid UTF-8", what));
+            None
+        }
+    }
+}
+
+/// Reads an array of 3 values, recording an error naming `what` if it's null.
+unsafe fn read_triple<T: Copy>(values: *const T, what: &str) -> Option<(T, T, T)> {
+    if values.is_null() {
+        set_last_error(format!("{} is null", what));
+        return None;
+    }
+
+    Some((*values, *values.add(1), *values.add(2)))
+}
+
+/// Creates a new machine, as [`Machine::try_new`]. Returns null on error.
+///
+/// # Safety
+///
+/// `rotors` must point to 3 null-terminated strings, `ring_settings` and `positions` must each
+/// point to 3 values, and `reflector` and `plugboard` must be null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn enigma_new(
+    rotors: *const *const c_char,
+    ring_settings: *const usize,
+    positions: *const usize,
+    reflector: *const c_char,
+    plugboard: *const c_char,
+) -> *mut Machine {
+    let Some((left, middle, right)) = read_triple(rotors, "rotors") else {
+        return ptr::null_mut();
+    };
+    let (Some(left), Some(middle), Some(right)) = (
+        read_str(left, "left rotor"),
+        read_str(middle, "middle rotor"),
+        read_str(right, "right rotor"),
+    ) else {
+        return ptr::null_mut();
+    };
+    let (Some(ring_settings), Some(positions)) = (
+        read_triple(ring_settings, "ring settings"),
+        read_triple(positions, "positions"),
+    ) else {
+        return ptr::null_mut();
+    };
+    let (Some(reflector), Some(plugboard)) = (
+        read_str(reflector, "reflector"),
+        read_str(plugboard, "plugboard"),
+    ) else {
+        return ptr::null_mut();
+    };
+
+    match Machine::try_new(
+        (left, middle, right),
+        ring_settings,
+        positions,
+        reflector,
+        plugboard,
+    ) {
+        Ok(machine) => Box::into_raw(Box::new(machine)),
+        Err(e) => {
+            set_last_error(e.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Encrypts `message` with the machine, as [`Machine::encrypt`]. Returns a new string that must
+/// be released with [`enigma_string_free`], or null on error.
+///
+/// # Safety
+///
+/// `machine` must be a pointer returned by [`enigma_new`] that hasn't been freed, and `message`
+/// must be a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn enigma_encrypt(
+    machine: *mut Machine,
+    message: *const c_char,
+) -> *mut c_char {
+    let Some(machine) = machine.as_mut() else {
+        set_last_error("machine is null");
+        return ptr::null_mut();
+    };
+    let Some(message) = read_str(message, "message") else {
+        return ptr::null_mut();
+    };
+
+    // The output only contains letters, or the input's characters with pass-through, so can't
+    // contain a nul byte.
+    match CString::new(machine.encrypt(message)) {
+        Ok(encrypted) => encrypted.into_raw(),
+        Err(_) => {
+            set_last_error("encrypted message contains a nul byte");
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Releases a machine created by [`enigma_new`]. Does nothing if `machine` is null.
+///
+/// # Safety
+///
+/// `machine` must be null or a pointer returned by [`enigma_new`] that hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn enigma_free(machine: *mut Machine) {
+    if !machine.is_null() {
+        drop(Box::from_raw(machine));
+    }
+}
+
+/// Releases a string returned by [`enigma_encrypt`]. Does nothing if `s` is null.
+///
+/// # Safety
+///
+/// `s` must be null or a pointer returned by [`enigma_encrypt`] that hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn enigma_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Describes the last error on the calling thread, or returns null if there hasn't been one. The
+/// string is owned by the library and is valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn enigma_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_machine(rotors: [&CStr; 3], reflector: &CStr, plugboard: &CStr) -> *mut Machine {
+        let rotors = rotors.map(CStr::as_ptr);
+        unsafe {
+            enigma_new(
+                rotors.as_ptr(),
+                [1, 1, 1].as_ptr(),
+                [0, 0, 0].as_ptr(),
+                reflector.as_ptr(),
+                plugboard.as_ptr(),
+            )
+        }
+    }
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(enigma_last_error()) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn test_encrypt() {
+        let machine = new_machine([c"I", c"II", c"III"], c"B", c"");
+        assert!(!machine.is_null());
+
+        unsafe {
+            let encrypted = enigma_encrypt(machine, c"EXAMPLEXMESSAGE".as_ptr());
+            assert_eq!(CStr::from_ptr(encrypted), c"AQTSWCKHEVCFYBS");
+            enigma_string_free(encrypted);
+            enigma_free(machine);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let machine = new_machine([c"I", c"IX", c"III"], c"B", c"");
+        assert!(machine.is_null());
+        assert_eq!(last_error(), "unknown rotor name 'IX'");
+
+        let machine = new_machine([c"I", c"II", c"III"], c"B", c"AB AC");
+        assert!(machine.is_null());
+        assert_eq!(last_error(), "letter 'A' is connected more than once");
+
+        unsafe {
+            assert!(enigma_encrypt(ptr::null_mut(), c"A".as_ptr()).is_null());
+            assert_eq!(last_error(), "machine is null");
+            enigma_free(ptr::null_mut());
+            enigma_string_free(ptr::null_mut());
+        }
+    }
+}
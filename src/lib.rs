@@ -1,15 +1,49 @@
 #![deny(missing_docs)]
 //! Implementation of the Enigma machine. Simulates a common 3-rotor machine with the common 8
-//! rotors and 3 reflectors. [Wikipedia](https://en.wikipedia.org/wiki/Enigma_machine) has a good
-//! overview.
+//! rotors and 3 reflectors, as well as the 4-rotor M4, the Enigma G and the railway Enigma, see
+//! [`EnigmaModel`].
+//! [Wikipedia](https://en.wikipedia.org/wiki/Enigma_machine) has a good overview.
+pub mod analysis;
+mod clock_int;
 mod components;
+mod composite;
+mod config;
+pub mod convert;
+pub mod crack;
+mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 mod machine;
+mod macros;
+mod operator;
+pub mod text;
+pub mod wirings;
 
-pub use machine::Machine;
+pub use clock_int::ClockInt;
+pub use components::{
+    Component, Plugboard, Reflector, Rotor, RotorFamily, RotorName, SteppingComponent,
+};
+pub use composite::CompositeMachine;
+pub use config::{encrypt_over_keys, MachineConfig};
+pub use error::InvalidArgsError;
+pub use machine::{DoubleStepMode, EncryptStats, EnigmaModel, Machine, StepOrder, StepPolicy};
+pub use operator::Operator;
+
+use convert::{char_to_wire, try_char_to_wire, wire_to_char};
 
 /// Size of the alphabet used. Fixes the size of the rotors. Currently this is A-Z.
 pub const ALPHABET_SIZE: usize = 26;
 
+/// Names of the rotors that can be passed to [`Machine::new`].
+pub fn available_rotors() -> &'static [&'static str] {
+    Rotor::available_names()
+}
+
+/// Names of the reflectors that can be passed to [`Machine::new`].
+pub fn available_reflectors() -> &'static [&'static str] {
+    Reflector::available_names()
+}
+
 pub(crate) fn identity_wiring() -> [u8; ALPHABET_SIZE] {
     [
         0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
@@ -17,24 +51,194 @@ pub(crate) fn identity_wiring() -> [u8; ALPHABET_SIZE] {
     ]
 }
 
-pub(crate) fn char_to_wire(c: char) -> u8 {
-    debug_assert!(c.is_alphabetic());
-    (c.to_ascii_uppercase() as u8) - 65
-}
-
-pub(crate) fn wire_to_char(w: u8) -> char {
-    debug_assert!(w < 26);
-    (w + 65) as char
-}
-
 pub(crate) fn encoding_to_wiring(encoding: impl Into<String>) -> [u8; ALPHABET_SIZE] {
     let encoding = encoding.into();
     debug_assert_eq!(encoding.len(), ALPHABET_SIZE);
 
     let mut wiring = identity_wiring();
-    for (c, w) in encoding.chars().into_iter().zip(wiring.iter_mut()) {
+    for (c, w) in encoding.chars().zip(wiring.iter_mut()) {
         *w = char_to_wire(c)
     }
 
     wiring
 }
+
+/// Inverse of [`encoding_to_wiring`], giving the letters that A-Z are wired to.
+pub(crate) fn wiring_to_encoding(wiring: &[u8; ALPHABET_SIZE]) -> String {
+    wiring.iter().map(|&w| wire_to_char(w)).collect()
+}
+
+/// The inverse of the permutation `wiring`, so that `inverse[wiring[i]] == i`. `wiring` must be
+/// a permutation.
+///
+/// Components call this once when they're built rather than for each letter, so it's written for
+/// clarity; `benches/encrypt.rs` compares it against an indexed loop.
+pub(crate) fn invert_wiring(wiring: &[u8; ALPHABET_SIZE]) -> [u8; ALPHABET_SIZE] {
+    let mut inverse = [0; ALPHABET_SIZE];
+    for (i, &w) in wiring.iter().enumerate() {
+        inverse[w as usize] = i as u8;
+    }
+    inverse
+}
+
+/// Checked version of [`encoding_to_wiring`] that requires `encoding` to contain each letter
+/// exactly once, so the wiring is a permutation and can be inverted.
+pub(crate) fn try_encoding_to_wiring(
+    encoding: impl Into<String>,
+) -> Result<[u8; ALPHABET_SIZE], InvalidArgsError> {
+    let encoding = encoding.into();
+    if encoding.len() != ALPHABET_SIZE || !encoding.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(InvalidArgsError::InvalidWiring(encoding));
+    }
+
+    let wiring = encoding_to_wiring(encoding.as_str());
+    if !is_permutation(&wiring) {
+        return Err(InvalidArgsError::InvalidWiring(encoding));
+    }
+
+    Ok(wiring)
+}
+
+/// Whether `wiring` contains each wire `0..ALPHABET_SIZE` exactly once.
+pub(crate) fn is_permutation(wiring: &[u8; ALPHABET_SIZE]) -> bool {
+    let mut seen = [false; ALPHABET_SIZE];
+    wiring
+        .iter()
+        .all(|&w| (w as usize) < ALPHABET_SIZE && !std::mem::replace(&mut seen[w as usize], true))
+}
+
+/// Checks that `wiring` is a permutation, giving an [`InvalidArgsError::InvalidWiring`] showing
+/// it as letters, with `?` for wires that are out of range, if not.
+pub(crate) fn check_wiring(wiring: &[u8; ALPHABET_SIZE]) -> Result<(), InvalidArgsError> {
+    if is_permutation(wiring) {
+        return Ok(());
+    }
+
+    Err(invalid_wiring(wiring))
+}
+
+/// Converts `bytes` of wire numbers to a wiring, checking there are [`ALPHABET_SIZE`] of them and
+/// that they're a permutation like [`check_wiring`] does.
+pub(crate) fn wiring_from_bytes(bytes: &[u8]) -> Result<[u8; ALPHABET_SIZE], InvalidArgsError> {
+    let wiring: [u8; ALPHABET_SIZE] = bytes.try_into().map_err(|_| invalid_wiring(bytes))?;
+    check_wiring(&wiring)?;
+    Ok(wiring)
+}
+
+/// An [`InvalidArgsError::InvalidWiring`] showing `wiring` as letters, with `?` for wires that are
+/// out of range.
+fn invalid_wiring(wiring: &[u8]) -> InvalidArgsError {
+    let encoding = wiring
+        .iter()
+        .map(|&w| {
+            if (w as usize) < ALPHABET_SIZE {
+                wire_to_char(w)
+            } else {
+                '?'
+            }
+        })
+        .collect();
+    InvalidArgsError::InvalidWiring(encoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wiring_to_encoding() {
+        assert_eq!(
+            wiring_to_encoding(&identity_wiring()),
+            "ABCDEFGHIJKLMNOPQRSTUVWXYZ"
+        );
+        assert_eq!(
+            wiring_to_encoding(&encoding_to_wiring("EKMFLGDQVZNTOWYHXUSPAIBRCJ")),
+            "EKMFLGDQVZNTOWYHXUSPAIBRCJ"
+        );
+    }
+
+    #[test]
+    fn test_invert_wiring() {
+        assert_eq!(invert_wiring(&identity_wiring()), identity_wiring());
+
+        for encoding in [wirings::ROTOR_I, wirings::ROTOR_VIII, wirings::BETA] {
+            let wiring = encoding_to_wiring(encoding);
+            let inverse = invert_wiring(&wiring);
+            for w in 0..ALPHABET_SIZE as u8 {
+                assert_eq!(inverse[wiring[w as usize] as usize], w);
+                assert_eq!(wiring[inverse[w as usize] as usize], w);
+            }
+        }
+    }
+
+    #[test]
+    fn test_encoding_to_wiring_ignores_case() {
+        let upper = "EKMFLGDQVZNTOWYHXUSPAIBRCJ";
+        let lower = upper.to_ascii_lowercase();
+        assert_eq!(
+            encoding_to_wiring(upper),
+            encoding_to_wiring(lower.as_str())
+        );
+        assert_eq!(
+            try_encoding_to_wiring(lower.as_str()),
+            Ok(encoding_to_wiring(upper))
+        );
+    }
+
+    #[test]
+    fn test_check_wiring() {
+        assert_eq!(check_wiring(&identity_wiring()), Ok(()));
+
+        let mut wiring = identity_wiring();
+        wiring[1] = 0;
+        assert_eq!(
+            check_wiring(&wiring),
+            Err(InvalidArgsError::InvalidWiring(
+                "AACDEFGHIJKLMNOPQRSTUVWXYZ".to_string()
+            ))
+        );
+        wiring[1] = 26;
+        assert_eq!(
+            check_wiring(&wiring),
+            Err(InvalidArgsError::InvalidWiring(
+                "A?CDEFGHIJKLMNOPQRSTUVWXYZ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_wiring_from_bytes() {
+        let wiring = encoding_to_wiring(wirings::ROTOR_II);
+        assert_eq!(wiring_from_bytes(&wiring), Ok(wiring));
+        assert_eq!(
+            wiring_from_bytes(&wiring[..3]),
+            Err(InvalidArgsError::InvalidWiring("AJD".to_string()))
+        );
+        assert!(wiring_from_bytes(&[0; 27]).is_err());
+        assert!(wiring_from_bytes(&[0; 26]).is_err());
+    }
+
+    #[test]
+    fn test_try_encoding_to_wiring() {
+        assert_eq!(
+            try_encoding_to_wiring("ABCDEFGHIJKLMNOPQRSTUVWXYZ"),
+            Ok(identity_wiring())
+        );
+        assert_eq!(
+            try_encoding_to_wiring("EKMFLGDQVZNTOWYHXUSPAIBRCJ"),
+            Ok(encoding_to_wiring("EKMFLGDQVZNTOWYHXUSPAIBRCJ"))
+        );
+
+        for encoding in [
+            "AAAAAAAAAAAAAAAAAAAAAAAAAA",
+            "ABCDEFGHIJKLMNOPQRSTUVWXYY",
+            "ABCDEFGHIJKLMNOPQRSTUVWXY",
+            "ABCDEFGHIJKLMNOPQRSTUVWXY1",
+        ] {
+            assert_eq!(
+                try_encoding_to_wiring(encoding),
+                Err(InvalidArgsError::InvalidWiring(encoding.to_string()))
+            );
+        }
+    }
+}
@@ -1,10 +1,44 @@
-use crate::components::{Plugboard, Reflector, Rotor};
-use crate::{char_to_wire, wire_to_char};
+use crate::components::{map_char, Component, Plugboard, Reflector, Rotor, SteppingComponent};
+use crate::macros::trace;
+use crate::text::DigitPolicy;
+use crate::{
+    char_to_wire, config, operator, text, wire_to_char, wirings, ClockInt, InvalidArgsError,
+    MachineConfig, RotorFamily, RotorName, ALPHABET_SIZE,
+};
+use std::borrow::Cow;
+use std::hash::Hash;
+use std::io::{self, Read, Write};
+use std::str::FromStr;
+
+/// Size of the chunks read by [`Machine::process_reader`].
+const PROCESS_BUFFER_SIZE: usize = 8 * 1024;
+
+/// The rotor and reflector positions before a step, as recorded by [`Machine::set_history`].
+type StepState = ((ClockInt, ClockInt, ClockInt), ClockInt);
+
+/// A substitution for each position of a rotor, indexed by position then wire.
+type PositionTables = [[u8; ALPHABET_SIZE]; ALPHABET_SIZE];
+
+/// Marks bytes that aren't ASCII letters in [`ASCII_TO_WIRE`].
+const NOT_A_LETTER: u8 = u8::MAX;
+
+/// Maps each byte to its wire, where `A` and `a` are 0, or to [`NOT_A_LETTER`].
+const ASCII_TO_WIRE: [u8; 256] = {
+    let mut table = [NOT_A_LETTER; 256];
+    let mut i = 0;
+    while i < ALPHABET_SIZE {
+        table[b'A' as usize + i] = i as u8;
+        table[b'a' as usize + i] = i as u8;
+        i += 1;
+    }
+    table
+};
 
 /// Type that implements the Enigma machine.
 ///
-/// Consists of 3 rotors, a reflector and a plugboard. The input flows through the components in
-/// the following order:
+/// Consists of 3 rotors, a reflector and a plugboard, plus a fourth rotor on the
+/// [`EnigmaModel::M4`], which is built with [`MachineConfig`]. The input flows through the
+/// components in the following order:
 /// 1. Plugboard
 /// 2. Left rotor
 /// 3. Middle rotor
@@ -15,88 +49,1465 @@ use crate::{char_to_wire, wire_to_char};
 /// 8. Left rotor
 /// 9. Plugboard
 ///
-/// Before the input flows through the machine, one or more rotors rotate (turnover).
+/// Before the input flows through the machine, one or more rotors rotate (turnover), so even the
+/// first letter is enciphered with the right rotor one step on from its initial position.
+///
+/// Ring settings and positions both count from 0, so `0` is `A`. Key sheets number ring settings
+/// from 01, so a ring setting of 01 is `0` here, 02 is `1` and so on.
 ///
 /// # Examples
 ///
 /// ```
 /// use enigma::Machine;
-/// let mut machine = Machine::new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "B", "");
+/// let mut machine = Machine::new(("I", "II", "III"), (0, 0, 0), (0, 0, 0), "B", "");
 /// let encrypted = machine.encrypt("EXAMPLEXMESSAGE");
-/// assert_eq!(encrypted, "AQTSWCKHEVCFYBS".to_string());
+/// assert_eq!(encrypted, "FSZOJIKAOCJABZS".to_string());
 ///
-/// let mut machine = Machine::new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "B", "");
+/// let mut machine = Machine::new(("I", "II", "III"), (0, 0, 0), (0, 0, 0), "B", "");
 /// let unencrypted = machine.encrypt(&encrypted);
 /// assert_eq!(unencrypted, "EXAMPLEXMESSAGE".to_string());
 /// ```
+///
+/// Two machines compare equal when they have the same rotors, ring settings, rotor positions,
+/// reflector, plugboard, options, [history](Machine::set_history) and starting positions, so a
+/// clone can be used to snapshot and restore the machine's state.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Machine {
     left_rotor: Rotor,
     middle_rotor: Rotor,
     right_rotor: Rotor,
     reflector: Reflector,
     plugboard: Plugboard,
+    step_order: StepOrder,
+    pass_through: bool,
+    history: Option<Vec<StepState>>,
+    model: EnigmaModel,
+    entry_wheel: Option<Rotor>,
+    greek_rotor: Option<Rotor>,
+    double_step: DoubleStepMode,
+    start: StepState,
 }
 
 impl Machine {
     /// Creates a new [`Machine`] from the given settings and initial state.
+    ///
+    /// Ring settings and positions count from 0, so the ring setting listed as 01 on a key sheet
+    /// is `0`. They're reduced modulo [`ALPHABET_SIZE`](crate::ALPHABET_SIZE) here, when they're
+    /// converted to [`ClockInt`]s, so a position of 27 is the same as 1. Use
+    /// [`Machine::ring_settings`] and [`Machine::positions`] to read back the reduced values, e.g.
+    /// to detect that a value wrapped.
+    ///
+    /// Unknown rotor and reflector names give identity components. Use [`Machine::try_new`] to get
+    /// an error instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plugboard connections are invalid, e.g. a letter is connected more than
+    /// once, as [`Plugboard::new`] would return an error.
     pub fn new(
         rotors: (&str, &str, &str),
         ring_settings: (usize, usize, usize),
         rotor_positions: (usize, usize, usize),
         reflector_type: &str,
         plugboard_connections: &str,
+    ) -> Self {
+        Self::from_components(
+            (
+                Rotor::from_name(rotors.0, ring_settings.0, rotor_positions.0),
+                Rotor::from_name(rotors.1, ring_settings.1, rotor_positions.1),
+                Rotor::from_name(rotors.2, ring_settings.2, rotor_positions.2),
+            ),
+            Reflector::from_name(reflector_type),
+            Plugboard::new(plugboard_connections).unwrap_or_else(|e| panic!("{}", e)),
+        )
+    }
+
+    /// Creates a new [`Machine`] like [`Machine::new`], returning an error if a rotor or reflector
+    /// name is unknown or the plugboard connections are invalid, rather than falling back to
+    /// identity components or panicking.
+    ///
+    /// The machine is an [`EnigmaModel::M3`], so this also returns an error if the rotors and
+    /// reflector aren't ones it could be fitted with, as [`MachineConfig::build`] does, e.g. a
+    /// Greek rotor or rotors from more than one [`RotorFamily`]. The same rotor may be used in
+    /// more than one slot; only [`Machine::validate_config`] checks for that.
+    pub fn try_new(
+        rotors: (&str, &str, &str),
+        ring_settings: (usize, usize, usize),
+        rotor_positions: (usize, usize, usize),
+        reflector_type: &str,
+        plugboard_connections: &str,
+    ) -> Result<Self, InvalidArgsError> {
+        Self::with_model(
+            EnigmaModel::M3,
+            rotors,
+            ring_settings,
+            rotor_positions,
+            reflector_type,
+            plugboard_connections,
+        )
+    }
+
+    /// Creates a new [`Machine`] like [`Machine::try_new`], but with the rotors given as
+    /// [`RotorName`]s so their names can't be mistyped. Returns an error if the reflector name is
+    /// unknown, the rotors and reflector don't fit an [`EnigmaModel::M3`] together, or the
+    /// plugboard connections are invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::{Machine, RotorName};
+    /// let rotors = (RotorName::I, RotorName::II, RotorName::III);
+    /// let mut machine = Machine::with_rotor_names(rotors, (0, 0, 0), (0, 0, 0), "B", "").unwrap();
+    /// assert_eq!(machine.encrypt("AAAAA"), "BDZGO");
+    /// ```
+    pub fn with_rotor_names(
+        rotors: (RotorName, RotorName, RotorName),
+        ring_settings: (usize, usize, usize),
+        rotor_positions: (usize, usize, usize),
+        reflector_type: &str,
+        plugboard_connections: &str,
+    ) -> Result<Self, InvalidArgsError> {
+        Self::try_new(
+            (rotors.0.as_str(), rotors.1.as_str(), rotors.2.as_str()),
+            ring_settings,
+            rotor_positions,
+            reflector_type,
+            plugboard_connections,
+        )
+    }
+
+    /// Checks the arguments of [`Machine::try_new`] without building a machine, returning every
+    /// problem found rather than only the first. The rotors and reflector must fit an
+    /// [`EnigmaModel::M3`] together, checked the same way as [`MachineConfig::build`] does.
+    ///
+    /// Unlike [`Machine::new`] and [`Machine::try_new`] this is strict: ring settings and positions
+    /// must be less than [`ALPHABET_SIZE`](crate::ALPHABET_SIZE), and each rotor may only be used
+    /// once, as on the real machine. Only this dry run checks for repeated rotors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::{InvalidArgsError, Machine};
+    /// let valid = Machine::validate_config(("I", "II", "III"), (0, 0, 0), (0, 0, 0), "B", "AB");
+    /// assert_eq!(valid, Ok(()));
+    ///
+    /// let invalid =
+    ///     Machine::validate_config(("I", "I", "IX"), (0, 0, 30), (0, 0, 0), "B", "AB AC");
+    /// let errors = invalid.unwrap_err();
+    /// assert_eq!(errors.len(), 4);
+    /// assert!(errors.contains(&InvalidArgsError::DuplicatePlug('A')));
+    /// ```
+    pub fn validate_config(
+        rotors: (&str, &str, &str),
+        ring_settings: (usize, usize, usize),
+        rotor_positions: (usize, usize, usize),
+        reflector_type: &str,
+        plugboard_connections: &str,
+    ) -> Result<(), Vec<InvalidArgsError>> {
+        let mut errors = Vec::new();
+
+        let rotors = [rotors.0, rotors.1, rotors.2];
+        let mut rotor_names = Vec::with_capacity(rotors.len());
+        for (i, name) in rotors.iter().enumerate() {
+            match RotorName::try_from(*name) {
+                Ok(rotor_name) => rotor_names.push(rotor_name),
+                Err(e) => errors.push(e),
+            }
+            if rotors[..i].iter().filter(|&r| r == name).count() == 1 {
+                // Only reported at the second use.
+                errors.push(InvalidArgsError::RepeatedRotor(name.to_string()));
+            }
+        }
+
+        for (setting, values) in [
+            ("ring setting", ring_settings),
+            ("rotor position", rotor_positions),
+        ] {
+            for value in [values.0, values.1, values.2] {
+                if value >= crate::ALPHABET_SIZE {
+                    errors.push(InvalidArgsError::SettingOutOfRange { setting, value });
+                }
+            }
+        }
+
+        let reflector = match Reflector::try_from_name(reflector_type) {
+            Ok(_) => Some(reflector_type),
+            Err(e) => {
+                errors.push(e);
+                None
+            }
+        };
+        errors.extend(config::model_errors(
+            EnigmaModel::M3,
+            &rotor_names,
+            reflector,
+        ));
+        errors.extend(Plugboard::parse(plugboard_connections).1);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Creates a new [`Machine`] from already constructed left, middle and right rotors, a
+    /// reflector and a plugboard.
+    pub(crate) fn from_components(
+        rotors: (Rotor, Rotor, Rotor),
+        reflector: Reflector,
+        plugboard: Plugboard,
+    ) -> Self {
+        let start = (
+            (
+                rotors.0.position(),
+                rotors.1.position(),
+                rotors.2.position(),
+            ),
+            reflector.position(),
+        );
+        Self {
+            left_rotor: rotors.0,
+            middle_rotor: rotors.1,
+            right_rotor: rotors.2,
+            reflector,
+            plugboard,
+            step_order: StepOrder::default(),
+            pass_through: false,
+            history: None,
+            model: EnigmaModel::default(),
+            entry_wheel: None,
+            greek_rotor: None,
+            double_step: DoubleStepMode::default(),
+            start,
+        }
+    }
+
+    /// Creates a [`Machine`] of the given model from its internal parts, for [`MachineConfig`].
+    pub(crate) fn from_model_components(
+        model: EnigmaModel,
+        double_step: DoubleStepMode,
+        greek_rotor: Option<Rotor>,
+        rotors: (Rotor, Rotor, Rotor),
+        reflector: Reflector,
+        plugboard: Plugboard,
     ) -> Self {
         Self {
-            left_rotor: Rotor::from_name(rotors.0, ring_settings.0, rotor_positions.0),
-            middle_rotor: Rotor::from_name(rotors.1, ring_settings.1, rotor_positions.1),
-            right_rotor: Rotor::from_name(rotors.2, ring_settings.2, rotor_positions.2),
-            reflector: Reflector::from_name(reflector_type),
-            plugboard: Plugboard::from_connections(plugboard_connections),
+            model,
+            double_step,
+            greek_rotor,
+            entry_wheel: model.entry_wheel(),
+            ..Self::from_components(rotors, reflector, plugboard)
         }
     }
 
-    fn rotate(&mut self) {
-        if self.middle_rotor.at_notch() {
+    /// Creates a new 3-rotor [`Machine`] of the given model, otherwise like [`Machine::try_new`].
+    /// A shorthand for [`MachineConfig`], which is needed for the 4-rotor
+    /// [`EnigmaModel::M4`].
+    ///
+    /// Returns an error if the rotors or reflector aren't ones the model could be fitted with,
+    /// e.g. the [`EnigmaModel::G`] takes the [`RotorFamily::G`](crate::RotorFamily::G) rotors
+    /// and reflector `"G"`. The G had no plugboard, so `plugboard_connections` would be empty, and
+    /// its reflector can be turned with [`Machine::set_reflector_position`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::{EnigmaModel, Machine};
+    /// let rotors = ("G-I", "G-II", "G-III");
+    /// let mut machine =
+    ///     Machine::with_model(EnigmaModel::G, rotors, (0, 0, 0), (0, 0, 0), "G", "").unwrap();
+    /// let encrypted = machine.encrypt("HELLOXWORLD");
+    ///
+    /// let mut machine =
+    ///     Machine::with_model(EnigmaModel::G, rotors, (0, 0, 0), (0, 0, 0), "G", "").unwrap();
+    /// assert_eq!(machine.encrypt(&encrypted), "HELLOXWORLD");
+    /// ```
+    pub fn with_model(
+        model: EnigmaModel,
+        rotors: (&str, &str, &str),
+        ring_settings: (usize, usize, usize),
+        rotor_positions: (usize, usize, usize),
+        reflector_type: &str,
+        plugboard_connections: &str,
+    ) -> Result<Self, InvalidArgsError> {
+        MachineConfig::new(model)
+            .rotors(&[rotors.0, rotors.1, rotors.2])
+            .ring_settings(&[ring_settings.0, ring_settings.1, ring_settings.2])
+            .positions(&[rotor_positions.0, rotor_positions.1, rotor_positions.2])
+            .reflector(reflector_type)
+            .plugboard(plugboard_connections)
+            .build()
+    }
+
+    /// Creates an [`EnigmaModel::G`] with the G reflector set to `reflector_position`. The G has
+    /// no plugboard, and the reflector is stepped by the gearing along with the rotors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::{ClockInt, Machine};
+    /// let rotors = ("G-I", "G-II", "G-III");
+    /// let mut machine = Machine::new_g(rotors, (0, 0, 0), (0, 0, 0), 5).unwrap();
+    /// assert_eq!(machine.reflector_position(), ClockInt::new(5));
+    /// let encrypted = machine.encrypt("HELLOXWORLD");
+    ///
+    /// let mut machine = Machine::new_g(rotors, (0, 0, 0), (0, 0, 0), 5).unwrap();
+    /// assert_eq!(machine.encrypt(&encrypted), "HELLOXWORLD");
+    /// ```
+    pub fn new_g(
+        rotors: (&str, &str, &str),
+        ring_settings: (usize, usize, usize),
+        rotor_positions: (usize, usize, usize),
+        reflector_position: usize,
+    ) -> Result<Self, InvalidArgsError> {
+        let mut machine = Self::with_model(
+            EnigmaModel::G,
+            rotors,
+            ring_settings,
+            rotor_positions,
+            "G",
+            "",
+        )?;
+        machine.set_reflector_position(reflector_position);
+        Ok(machine)
+    }
+
+    /// The standard Enigma I starting configuration: rotors I, II and III, ring settings 1, 1, 1
+    /// (`A`, which is 0 here), positions `AAA`, reflector B and no plugs. For other settings use
+    /// [`Machine::new`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::Machine;
+    /// let mut machine = Machine::default_m3();
+    /// assert_eq!(machine.encrypt("AAAAA"), "BDZGO");
+    /// ```
+    pub fn default_m3() -> Self {
+        Self::new(("I", "II", "III"), (0, 0, 0), (0, 0, 0), "B", "")
+    }
+
+    /// Creates a machine from settings in environment variables, for configuring it from a
+    /// shell script without long argument lists:
+    ///
+    /// | Variable           | Format                                       | Default    |
+    /// |--------------------|----------------------------------------------|------------|
+    /// | `ENIGMA_ROTORS`    | Comma-separated rotor names, e.g. `I,II,III` | `I,II,III` |
+    /// | `ENIGMA_RINGS`     | Comma-separated numbers from 0 or letters    | all 0      |
+    /// | `ENIGMA_POSITIONS` | Comma-separated numbers from 0 or letters    | all 0      |
+    /// | `ENIGMA_REFLECTOR` | Reflector name                               | `B`        |
+    /// | `ENIGMA_PLUGBOARD` | Pairs of letters, e.g. `AB CD`               | no plugs   |
+    ///
+    /// The values are read the same way as the segments of a key string parsed by
+    /// [`MachineConfig`]. Four rotors select the [`EnigmaModel::M4`], whose reflector defaults to
+    /// `B-THIN` instead. Gives an error if a variable is set but malformed.
+    pub fn from_env() -> Result<Self, InvalidArgsError> {
+        MachineConfig::from_vars(|name| std::env::var(name))?.build()
+    }
+
+    /// The model being simulated.
+    pub fn model(&self) -> EnigmaModel {
+        self.model
+    }
+
+    /// Sets whether the rotors step before or after each letter is enciphered. Defaults to
+    /// [`StepOrder::Before`], like the real machine.
+    pub fn set_step_order(&mut self, step_order: StepOrder) {
+        self.step_order = step_order;
+    }
+
+    /// Sets whether characters that aren't ASCII letters are copied to the output unchanged
+    /// rather than left out of it. Off by default.
+    pub fn set_pass_through(&mut self, pass_through: bool) {
+        self.pass_through = pass_through;
+    }
+
+    /// Sets whether the rotor positions before each step are recorded, so that
+    /// [`Machine::undo`] can step back. Off by default. Turning it off discards the history.
+    ///
+    /// The history grows by 4 bytes for every step taken, i.e. for every letter enciphered, until
+    /// it's cleared with [`Machine::clear_history`].
+    pub fn set_history(&mut self, enabled: bool) {
+        self.history = enabled.then(Vec::new);
+    }
+
+    /// Discards the recorded history, keeping recording on if it was.
+    pub fn clear_history(&mut self) {
+        if let Some(history) = &mut self.history {
+            history.clear();
+        }
+    }
+
+    /// Reverts the most recent step, e.g. from [`Machine::encrypt_char`], restoring the rotor
+    /// positions from before it. Returns whether there was a recorded step to undo, which there
+    /// never is unless [`Machine::set_history`] is on.
+    ///
+    /// Stepping can't simply be run backwards because of the double step, hence the recorded
+    /// positions.
+    pub fn undo(&mut self) -> bool {
+        match self.history.as_mut().and_then(Vec::pop) {
+            Some(state) => {
+                self.move_to(state);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the rotors and reflector to their starting positions, e.g. to encrypt another
+    /// message with the same key. These are the positions the machine was created with, or the
+    /// last ones given to [`Machine::set_positions`] and [`Machine::set_reflector_position`].
+    /// Also clears the [history](Machine::set_history).
+    pub fn reset(&mut self) {
+        self.move_to(self.start);
+        self.clear_history();
+    }
+
+    /// Moves the rotors and reflector without changing the starting positions.
+    fn move_to(&mut self, (positions, reflector_position): StepState) {
+        self.left_rotor.set_position(positions.0);
+        self.middle_rotor.set_position(positions.1);
+        self.right_rotor.set_position(positions.2);
+        self.reflector.set_position(reflector_position);
+    }
+
+    /// Current positions of the left, middle and right rotors.
+    pub fn positions(&self) -> (ClockInt, ClockInt, ClockInt) {
+        (
+            self.left_rotor.position(),
+            self.middle_rotor.position(),
+            self.right_rotor.position(),
+        )
+    }
+
+    /// The positions of the left, middle and right rotors after `n` more letters, found by
+    /// stepping a copy of the machine without enciphering anything. Useful for lining up with a
+    /// point in a long message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::Machine;
+    /// let mut machine = Machine::default_m3();
+    /// let positions = machine.positions_after(1000);
+    /// machine.encrypt(&"A".repeat(1000));
+    /// assert_eq!(machine.positions(), positions);
+    /// ```
+    pub fn positions_after(&self, n: usize) -> (ClockInt, ClockInt, ClockInt) {
+        let mut machine = self.clone();
+        machine.history = None;
+        machine.fast_forward(n);
+        machine.positions()
+    }
+
+    /// Steps the rotors as `n` letters would, without enciphering anything, e.g. to resume a
+    /// message part way through. The steps are recorded for [`Machine::undo`] if history is on,
+    /// and [`Machine::reset`] still returns to the positions before them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::Machine;
+    /// let message = "ANGRIFFXUMXVIERXUHR";
+    /// let expected = Machine::default_m3().encrypt(message);
+    ///
+    /// let mut machine = Machine::default_m3();
+    /// machine.fast_forward(8);
+    /// assert_eq!(machine.encrypt(&message[8..]), expected[8..]);
+    /// ```
+    pub fn fast_forward(&mut self, n: usize) {
+        for _ in 0..n {
+            self.rotate();
+        }
+    }
+
+    /// How many more key presses until the left rotor first steps, counting the press that steps
+    /// it, found by stepping a copy of the machine like [`Machine::positions_after`]. This
+    /// includes the double step of the middle rotor, so from `ADU` with rotors I, II and III it's
+    /// the third press.
+    ///
+    /// Returns `None` if the left rotor never steps, e.g. if the right or middle rotor has no
+    /// notches.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::Machine;
+    /// let mut machine = Machine::default_m3();
+    /// assert_eq!(machine.keypresses_to_left_step(), Some(101));
+    /// machine.set_positions((0usize, 3, 20));
+    /// assert_eq!(machine.keypresses_to_left_step(), Some(3));
+    /// ```
+    pub fn keypresses_to_left_step(&self) -> Option<usize> {
+        let mut machine = self.clone();
+        machine.history = None;
+        let left = machine.left_rotor.position();
+
+        // The right rotor reaches a notch within a turn, and then the middle rotor within a turn
+        // of its own, so the left rotor steps within this many presses if it ever does.
+        (1..=(ALPHABET_SIZE + 1) * ALPHABET_SIZE).find(|_| {
+            machine.rotate();
+            machine.left_rotor.position() != left
+        })
+    }
+
+    /// Sets the positions of the left, middle and right rotors, e.g. `('A', 'B', 'C')` or
+    /// `(0usize, 1, 2)`. Numbers are reduced modulo [`ALPHABET_SIZE`](crate::ALPHABET_SIZE).
+    /// These become the positions [`Machine::reset`] returns to.
+    pub fn set_positions<T: Into<ClockInt>>(&mut self, positions: (T, T, T)) {
+        self.left_rotor.set_position(positions.0);
+        self.middle_rotor.set_position(positions.1);
+        self.right_rotor.set_position(positions.2);
+        self.start.0 = self.positions();
+    }
+
+    /// Current position of the reflector, which only changes on the [`EnigmaModel::G`].
+    pub fn reflector_position(&self) -> ClockInt {
+        self.reflector.position()
+    }
+
+    /// Sets the position of the reflector. See [`Reflector::set_position`].
+    pub fn set_reflector_position(&mut self, position: impl Into<ClockInt>) {
+        self.reflector.set_position(position);
+        self.start.1 = self.reflector.position();
+    }
+
+    /// The name of the reflector, e.g. `"B"`, or its wiring if it isn't a historical one.
+    pub fn reflector_name(&self) -> String {
+        self.reflector.name()
+    }
+
+    /// Swaps in the historical reflector `reflector_type`, one of [`EnigmaModel::reflectors`] for
+    /// the machine's model. The rotors and reflector keep their positions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::Machine;
+    /// let mut machine = Machine::default_m3();
+    /// machine.set_reflector("C").unwrap();
+    /// assert_eq!(machine.reflector_name(), "C");
+    /// assert!(machine.set_reflector("B-THIN").is_err());
+    /// ```
+    pub fn set_reflector(&mut self, reflector_type: &str) -> Result<(), InvalidArgsError> {
+        let mut reflector = Reflector::try_from_name(reflector_type)?;
+        if !self.model.reflectors().contains(&reflector_type) {
+            return Err(InvalidArgsError::NotInModel(reflector_type.to_string()));
+        }
+
+        reflector.set_position(self.reflector.position());
+        self.reflector = reflector;
+        Ok(())
+    }
+
+    /// Replaces the plugboard with one made from `connections`, as accepted by [`Plugboard::new`].
+    /// The rotors and reflector are left as they are, so the plugboard can be changed between
+    /// attempts without rebuilding the machine. On error the plugboard is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::Machine;
+    /// let mut machine = Machine::default_m3();
+    /// machine.set_plugboard("AB CD").unwrap();
+    /// assert!(machine.set_plugboard("AB AC").is_err());
+    /// machine.clear_plugboard();
+    /// assert_eq!(machine.encrypt("AAAAA"), "BDZGO");
+    /// ```
+    pub fn set_plugboard(&mut self, connections: &str) -> Result<(), InvalidArgsError> {
+        self.plugboard = Plugboard::new(connections)?;
+        Ok(())
+    }
+
+    /// Removes every plugboard connection, leaving each letter connected to itself.
+    pub fn clear_plugboard(&mut self) {
+        self.plugboard = Plugboard::identity();
+    }
+
+    /// Ring settings of the left, middle and right rotors.
+    pub fn ring_settings(&self) -> (ClockInt, ClockInt, ClockInt) {
+        (
+            self.left_rotor.ring_setting(),
+            self.middle_rotor.ring_setting(),
+            self.right_rotor.ring_setting(),
+        )
+    }
+
+    /// A fingerprint of the machine's state that's equal for any two machines that will encrypt
+    /// every message the same way from here on, so a key search can skip equivalent states already
+    /// tried by keeping the fingerprints in a [`HashSet`](std::collections::HashSet).
+    ///
+    /// On the models other than the [`EnigmaModel::G`], collapses the left rotor's ring setting
+    /// and position into their difference, since both only shift the left rotor's contacts and the
+    /// left rotor never steps on its own. The left rotor's notches are ignored as nothing sits to
+    /// its left. Every other setting is kept, so machines with different fingerprints may still be
+    /// equivalent.
+    pub fn canonical_key(&self) -> impl Hash + Eq {
+        let left_rotor = match self.model {
+            EnigmaModel::M3 | EnigmaModel::M4 | EnigmaModel::Railway => {
+                self.left_rotor.canonical_left()
+            }
+            // The left rotor's notches step the reflector.
+            EnigmaModel::G => self.left_rotor.clone(),
+        };
+        (
+            self.model,
+            self.double_step,
+            self.greek_rotor.as_ref().map(Rotor::canonical_left),
+            left_rotor,
+            self.middle_rotor.clone(),
+            self.right_rotor.clone(),
+            self.reflector.clone(),
+            self.plugboard.clone(),
+            self.step_order,
+            self.pass_through,
+        )
+    }
+
+    /// A checksum of the machine's current settings, to catch mistakes when they're copied out by
+    /// hand. Two machines with the same settings have the same checksum.
+    ///
+    /// This is the Fletcher-16 checksum of the ASCII settings string
+    /// `ROTORS/RINGS/POSITIONS/REFLECTOR/PLUGS`. It looks like the key that [`Machine`]'s
+    /// [`FromStr`] implementation reads, but it's only written for the checksum and can't always be
+    /// read back, as it may hold a reflector position or custom wirings:
+    ///
+    /// * `ROTORS` are the rotor names from left to right, e.g. `BETA,I,II,III` on the
+    ///   [`EnigmaModel::M4`], comma-separated. A custom rotor is given by its wiring instead.
+    /// * `RINGS` and `POSITIONS` are the ring settings and positions of the same rotors, as
+    ///   comma-separated letters. On the [`EnigmaModel::G`], or if the reflector has been turned
+    ///   away from `A`, the reflector's position is added at the end of `POSITIONS`.
+    /// * `REFLECTOR` is the reflector name, or its wiring for a custom reflector.
+    /// * `PLUGS` are the plugboard pairs, each in alphabetical order and sorted by their first
+    ///   letter, space-separated, e.g. `AB CZ`. This may be empty.
+    ///
+    /// With the bytes of the string as `b`, the checksum is `(sum2 << 8) | sum1`, where for each
+    /// byte in turn `sum1 = (sum1 + b) % 255` and `sum2 = (sum2 + sum1) % 255`, both starting at 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::Machine;
+    /// // The settings string is "I,II,III/A,A,A/A,A,A/B/".
+    /// assert_eq!(Machine::default_m3().settings_checksum(), 0xA647);
+    /// ```
+    pub fn settings_checksum(&self) -> u16 {
+        let (sum1, sum2) = self
+            .settings_string()
+            .bytes()
+            .fold((0u16, 0u16), |(sum1, sum2), b| {
+                let sum1 = (sum1 + b as u16) % 255;
+                (sum1, (sum2 + sum1) % 255)
+            });
+        (sum2 << 8) | sum1
+    }
+
+    /// The string [`Machine::settings_checksum`] is taken over.
+    fn settings_string(&self) -> String {
+        let rotors: Vec<&Rotor> = self
+            .greek_rotor
+            .iter()
+            .chain([&self.left_rotor, &self.middle_rotor, &self.right_rotor])
+            .collect();
+        let join = |values: Vec<String>| values.join(",");
+
+        let names = join(rotors.iter().map(|r| r.name()).collect());
+        let rings = join(
+            rotors
+                .iter()
+                .map(|r| r.ring_setting().to_string())
+                .collect(),
+        );
+        let mut positions: Vec<String> = rotors.iter().map(|r| r.position().to_string()).collect();
+        if self.model.has_stepping_reflector() || self.reflector.position() != ClockInt::default() {
+            positions.push(self.reflector.position().to_string());
+        }
+
+        format!(
+            "{}/{}/{}/{}/{}",
+            names,
+            rings,
+            join(positions),
+            self.reflector.name(),
+            self.plugboard.connections()
+        )
+    }
+
+    /// Steps the rotors, returning how many of them moved.
+    fn rotate(&mut self) -> usize {
+        let state = (self.positions(), self.reflector.position());
+        if let Some(history) = &mut self.history {
+            history.push(state);
+        }
+
+        let steps = match self.model {
+            EnigmaModel::M3 | EnigmaModel::M4 | EnigmaModel::Railway => self.rotate_ratchets(),
+            EnigmaModel::G => self.rotate_gears(),
+        };
+        trace!(
+            steps,
+            left = %self.left_rotor.position().to_char(),
+            middle = %self.middle_rotor.position().to_char(),
+            right = %self.right_rotor.position().to_char(),
+            "stepped rotors"
+        );
+        steps
+    }
+
+    /// Steps the rotors with the pawl and ratchet mechanism, including the double step of the
+    /// middle rotor unless it's turned off with [`DoubleStepMode::None`].
+    fn rotate_ratchets(&mut self) -> usize {
+        let mut steps = 1;
+        if self.double_step == DoubleStepMode::None {
+            if self.right_rotor.at_notch() {
+                if self.middle_rotor.at_notch() {
+                    self.left_rotor.turnover();
+                    steps += 1;
+                }
+                self.middle_rotor.turnover();
+                steps += 1;
+            }
+        } else if self.middle_rotor.at_notch() {
             self.middle_rotor.turnover();
             self.left_rotor.turnover();
+            steps += 2;
         } else if self.right_rotor.at_notch() {
             self.middle_rotor.turnover();
+            steps += 1;
         }
 
         self.right_rotor.turnover();
+        steps
     }
 
-    /// Encrypts a `message` using the machine.
-    pub fn encrypt(&mut self, message: &str) -> String {
+    /// Steps the rotors and reflector like an odometer, each turning over the next only as it
+    /// steps on from a notch.
+    fn rotate_gears(&mut self) -> usize {
+        let mut steps = 1;
+        if self.right_rotor.at_notch() {
+            if self.middle_rotor.at_notch() {
+                if self.left_rotor.at_notch() {
+                    self.reflector.rotate();
+                    steps += 1;
+                }
+                self.left_rotor.turnover();
+                steps += 1;
+            }
+            self.middle_rotor.turnover();
+            steps += 1;
+        }
+
+        self.right_rotor.turnover();
+        steps
+    }
+
+    /// Encrypts a single character like [`Machine::encrypt`], returning `None` if it isn't an
+    /// ASCII letter, in which case the rotors don't step.
+    pub fn encrypt_char(&mut self, c: char) -> Option<char> {
+        if !c.is_ascii_alphabetic() {
+            return None;
+        }
+
+        Some(wire_to_char(self.step_and_encipher(char_to_wire(c)).0))
+    }
+
+    /// Encrypts a single character like [`Machine::encrypt_char`], also returning the letters
+    /// showing in the left, middle and right rotor windows once the key press has stepped the
+    /// rotors, e.g. for animating a keyboard and lampboard.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::Machine;
+    /// let mut machine = Machine::default_m3();
+    /// assert_eq!(machine.encrypt_char_indexed('A'), Some(('B', ('A', 'A', 'B'))));
+    /// assert_eq!(machine.encrypt_char_indexed(' '), None);
+    /// ```
+    pub fn encrypt_char_indexed(&mut self, c: char) -> Option<(char, (char, char, char))> {
+        let encrypted = self.encrypt_char(c)?;
+        let (left, middle, right) = self.positions();
+        Some((
+            encrypted,
+            (left.to_char(), middle.to_char(), right.to_char()),
+        ))
+    }
+
+    /// The letter `c` is swapped with on the plugboard, or `c` itself if it isn't plugged, for
+    /// checking the plugboard on its own. Doesn't step the rotors.
+    ///
+    /// `c` may be in either case and the result is uppercase.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `c` isn't an ASCII letter.
+    pub fn encipher_plugboard_only(&self, c: char) -> char {
+        map_char(c, |l| self.plugboard.forward(l))
+    }
+
+    /// The letter `c` comes back as from the rotors and reflector, as if there were no plugboard,
+    /// for checking them on their own. Doesn't step the rotors, so this is the substitution at
+    /// the current positions, before the next key press steps them.
+    ///
+    /// Enciphering a letter at these positions goes through the plugboard, then this, then the
+    /// plugboard again.
+    ///
+    /// `c` may be in either case and the result is uppercase.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `c` isn't an ASCII letter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::{Machine, StepOrder};
+    /// let mut machine = Machine::new(("I", "II", "III"), (0, 0, 0), (0, 0, 0), "B", "AB");
+    /// let plugged = machine.encipher_plugboard_only('A');
+    /// let expected = machine.encipher_plugboard_only(machine.encipher_rotors_only(plugged));
+    ///
+    /// machine.set_step_order(StepOrder::After);
+    /// assert_eq!(machine.encrypt_char('A'), Some(expected));
+    /// ```
+    pub fn encipher_rotors_only(&self, c: char) -> char {
+        map_char(c, |l| self.encipher_rotors(l))
+    }
+
+    /// The whole substitution the machine makes at its current positions, plugboard included, as
+    /// the letters `A`-`Z` become, e.g. `"KM..."` for A to K and B to M. Doesn't step the rotors,
+    /// so unless the step order is [`StepOrder::After`] the next key press steps before using a
+    /// different substitution.
+    ///
+    /// As the machine is its own inverse, the substitution always swaps letters in pairs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::{Machine, StepOrder};
+    /// let mut machine = Machine::default_m3();
+    /// machine.set_step_order(StepOrder::After);
+    /// let substitution = machine.current_substitution();
+    /// assert_eq!(substitution.as_bytes()[0], b'U');
+    /// assert_eq!(machine.encrypt("A"), "U");
+    /// ```
+    pub fn current_substitution(&self) -> String {
+        (0..ALPHABET_SIZE as u8)
+            .map(|w| wire_to_char(self.encipher_letter(w)))
+            .collect()
+    }
+
+    /// Encrypts a `message` like [`Machine::encrypt`], returning each character enciphered
+    /// alongside what it became, e.g. for showing them side by side.
+    ///
+    /// Characters that aren't ASCII letters are left out, or paired with themselves if
+    /// [`Machine::set_pass_through`] is on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::Machine;
+    /// let mut machine = Machine::default_m3();
+    /// assert_eq!(machine.encrypt_pairs("a A"), vec![('a', 'B'), ('A', 'D')]);
+    /// ```
+    pub fn encrypt_pairs(&mut self, message: &str) -> Vec<(char, char)> {
         message
+            .chars()
+            .filter_map(|c| match self.encrypt_char(c) {
+                Some(e) => Some((c, e)),
+                None => self.pass_through.then_some((c, c)),
+            })
+            .collect()
+    }
+
+    /// Encrypts a `message` like [`Machine::encrypt`], but paced for playback: waits `per_char`
+    /// before passing each output character to `on_char`, e.g. to light up a lampboard at the
+    /// speed of an operator. Characters left out of the output don't take any time.
+    ///
+    /// This blocks the current thread with [`std::thread::sleep`] for the whole message, so run
+    /// it on its own thread in anything interactive. Needs the `std` feature, which is on by
+    /// default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::Machine;
+    /// use std::time::Duration;
+    /// let mut lamps = String::new();
+    /// let mut machine = Machine::default_m3();
+    /// machine.encrypt_timed("AAAAA", Duration::from_millis(1), |c| lamps.push(c));
+    /// assert_eq!(lamps, "BDZGO");
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn encrypt_timed(
+        &mut self,
+        message: &str,
+        per_char: std::time::Duration,
+        mut on_char: impl FnMut(char),
+    ) {
+        for c in message.chars() {
+            let encrypted = match self.encrypt_char(c) {
+                Some(e) => e,
+                None if self.pass_through => c,
+                None => continue,
+            };
+            std::thread::sleep(per_char);
+            on_char(encrypted);
+        }
+    }
+
+    /// Encrypts a `message` using the machine. Characters that aren't ASCII letters are skipped
+    /// without stepping the rotors, i.e. [`StepPolicy::SkipNonLetters`], and are left out of the
+    /// output unless [`Machine::set_pass_through`] is on.
+    pub fn encrypt(&mut self, message: &str) -> String {
+        self.encrypt_with_stats(message).0
+    }
+
+    /// Encrypts a `message` like [`Machine::encrypt`], but borrows it rather than allocating when
+    /// it has no ASCII letters, so nothing is enciphered. The result is then `message` itself if
+    /// [`Machine::set_pass_through`] is on, or empty otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::Machine;
+    /// use std::borrow::Cow;
+    /// let mut machine = Machine::default_m3();
+    /// machine.set_pass_through(true);
+    /// assert!(matches!(machine.encrypt_cow("{1: [2, 3]}"), Cow::Borrowed("{1: [2, 3]}")));
+    /// assert_eq!(machine.encrypt_cow("{a: 1}"), "{B: 1}");
+    /// ```
+    pub fn encrypt_cow<'a>(&mut self, message: &'a str) -> Cow<'a, str> {
+        if message.bytes().any(|b| b.is_ascii_alphabetic()) {
+            Cow::Owned(self.encrypt(message))
+        } else if self.pass_through {
+            Cow::Borrowed(message)
+        } else {
+            Cow::Borrowed("")
+        }
+    }
+
+    /// Encrypts a `message` after replacing its digits according to `policy`, e.g. spelling them
+    /// out with [`text::GERMAN_DIGITS`]. With [`DigitPolicy::Skip`], the default, this is the same
+    /// as [`Machine::encrypt`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::text::{DigitPolicy, GERMAN_DIGITS};
+    /// use enigma::Machine;
+    /// let policy = DigitPolicy::Replace(&GERMAN_DIGITS);
+    /// let encrypted = Machine::default_m3().encrypt_with_digit_policy("U 534", policy);
+    /// assert_eq!(encrypted.len(), "UFUENFDREIVIER".len());
+    ///
+    /// let decrypted = Machine::default_m3().decrypt_with_digit_policy(&encrypted, policy);
+    /// assert_eq!(decrypted, "U534");
+    /// ```
+    pub fn encrypt_with_digit_policy(&mut self, message: &str, policy: DigitPolicy<'_>) -> String {
+        self.encrypt(&text::replace_digits(message, policy))
+    }
+
+    /// Decrypts a `message` encrypted with [`Machine::encrypt_with_digit_policy`], turning the
+    /// codes for digits back into digits with [`text::restore_digits`].
+    pub fn decrypt_with_digit_policy(&mut self, message: &str, policy: DigitPolicy<'_>) -> String {
+        text::restore_digits(&self.encrypt(message), policy)
+    }
+
+    /// Encrypts a `message` after replacing punctuation and digits with letters according to
+    /// [`DEFAULT_NORMALIZATION`](crate::text::DEFAULT_NORMALIZATION), so natural text can be
+    /// sent.
+    pub fn encrypt_normalized(&mut self, message: &str) -> String {
+        self.encrypt_normalized_with(message, text::DEFAULT_NORMALIZATION)
+    }
+
+    /// Encrypts `text` like [`Machine::encrypt`], but keeps its line breaks, so the output has the
+    /// same lines as the input. Both `\n` and `\r\n` line endings are kept as they are.
+    ///
+    /// The rotors carry on stepping from one line to the next, so the lines are enciphered as one
+    /// continuous message. Use [`Machine::reset`] between lines to encipher them separately.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::Machine;
+    /// let mut machine = Machine::default_m3();
+    /// assert_eq!(machine.encrypt_lines("AAA\nAAA\n"), "BDZ\nGOW\n");
+    /// ```
+    pub fn encrypt_lines(&mut self, text: &str) -> String {
+        self.encrypt_lines_with_stats(text).0
+    }
+
+    /// Does the same as [`Machine::encrypt_lines`], also returning counts of what was encrypted.
+    /// Line endings aren't counted as skipped characters.
+    pub fn encrypt_lines_with_stats(&mut self, text: &str) -> (String, EncryptStats) {
+        let mut encrypted = String::with_capacity(text.len());
+        let mut total = EncryptStats::default();
+        for line in text.split_inclusive('\n') {
+            let content = line.trim_end_matches('\n').trim_end_matches('\r');
+            let (line_encrypted, stats) = self.encrypt_with_stats(content);
+            encrypted.push_str(&line_encrypted);
+            encrypted.push_str(&line[content.len()..]);
+
+            total.letters += stats.letters;
+            total.skipped += stats.skipped;
+            total.total_steps += stats.total_steps;
+        }
+        (encrypted, total)
+    }
+
+    /// Encrypts a `message` after replacing characters according to a custom normalisation
+    /// `table`. See [`text::normalize`].
+    pub fn encrypt_normalized_with(&mut self, message: &str, table: &[(char, &str)]) -> String {
+        self.encrypt(&text::normalize(message, table))
+    }
+
+    /// Encrypts a message following the German indicator procedure used until 1940, like
+    /// [`Operator::transmit`](crate::Operator::transmit) but on this machine.
+    ///
+    /// The rotors are set to the day's basic position `grund`, the operator's chosen message key
+    /// `msg_key` is enciphered twice to form a six-letter indicator, and then the rotors are set to
+    /// the message key to encipher the `body`. Returns the indicator followed by the enciphered
+    /// body.
+    ///
+    /// Returns an error if `grund` or `msg_key` isn't three ASCII letters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enigma::Machine;
+    /// let mut machine = Machine::new(("I", "II", "III"), (0, 0, 0), (0, 0, 0), "B", "");
+    /// let encrypted = machine.encrypt_with_indicator_procedure("ABC", "XYZ", "HELLO").unwrap();
+    /// assert_eq!(encrypted.len(), 11);
+    /// assert!(machine.encrypt_with_indicator_procedure("ABC", "XY", "HELLO").is_err());
+    /// ```
+    pub fn encrypt_with_indicator_procedure(
+        &mut self,
+        grund: &str,
+        msg_key: &str,
+        body: &str,
+    ) -> Result<String, InvalidArgsError> {
+        let grund = operator::parse_three_letters(grund, "Grundstellung")?;
+        let (indicator, body) = operator::encipher_with_indicator(self, grund, msg_key, body)?;
+        Ok(indicator + &body)
+    }
+
+    /// Encrypts ASCII bytes using the machine, like [`Machine::encrypt`] but without going through
+    /// `str`. The letters `A-Z` and `a-z` are enciphered to uppercase letters, and all other bytes
+    /// are skipped or passed through according to [`Machine::set_pass_through`].
+    ///
+    /// This takes a faster, table-driven path than [`Machine::encrypt`], so prefer it for long
+    /// messages. The output is the same.
+    pub fn encrypt_bytes(&mut self, input: &[u8]) -> Vec<u8> {
+        self.encrypt_bytes_with_stats(
+            input,
+            &mut EncryptStats::default(),
+            StepPolicy::SkipNonLetters,
+        )
+    }
+
+    /// Encrypts everything read from `reader` like [`Machine::encrypt_bytes`], writing the output
+    /// to `writer` as it goes rather than holding the whole message in memory.
+    ///
+    /// Characters that aren't ASCII letters don't step the rotors, and are left out of the output
+    /// unless [`Machine::set_pass_through`] is on. The writer isn't flushed.
+    pub fn process_reader<R: Read, W: Write>(&mut self, reader: R, writer: W) -> io::Result<()> {
+        self.process_reader_with_stats(reader, writer).map(|_| ())
+    }
+
+    /// Does the same as [`Machine::process_reader`], also returning counts of what was encrypted.
+    pub fn process_reader_with_stats<R: Read, W: Write>(
+        &mut self,
+        reader: R,
+        writer: W,
+    ) -> io::Result<EncryptStats> {
+        self.process_reader_with_policy(reader, writer, StepPolicy::SkipNonLetters)
+    }
+
+    /// Does the same as [`Machine::process_reader_with_stats`], choosing whether characters that
+    /// aren't ASCII letters step the rotors, like [`Machine::encrypt_with_policy`].
+    pub fn process_reader_with_policy<R: Read, W: Write>(
+        &mut self,
+        mut reader: R,
+        mut writer: W,
+        policy: StepPolicy,
+    ) -> io::Result<EncryptStats> {
+        let mut stats = EncryptStats::default();
+        let mut buffer = [0; PROCESS_BUFFER_SIZE];
+        loop {
+            let n = match reader.read(&mut buffer) {
+                Ok(0) => return Ok(stats),
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            writer.write_all(&self.encrypt_bytes_with_stats(&buffer[..n], &mut stats, policy))?;
+        }
+    }
+
+    /// The fast path behind [`Machine::encrypt_bytes`] and [`Machine::process_reader`], giving
+    /// exactly the same output as enciphering letter by letter.
+    ///
+    /// Everything left of the right rotor only moves when the middle rotor steps, so that part of
+    /// the path is folded into a table that's rebuilt when it does. The plugboard, entry wheel and
+    /// right rotor are folded into a table for each position of the right rotor, built once per
+    /// call. This leaves 3 lookups per letter, plus the stepping.
+    fn encrypt_bytes_with_stats(
+        &mut self,
+        input: &[u8],
+        stats: &mut EncryptStats,
+        policy: StepPolicy,
+    ) -> Vec<u8> {
+        let (inward, outward) = self.right_tables();
+        let mut core_state = self.core_state();
+        let mut core = self.core_table();
+
+        let mut output = Vec::with_capacity(input.len());
+        for &b in input {
+            let w = ASCII_TO_WIRE[b as usize];
+            if w == NOT_A_LETTER {
+                trace!(byte = %b.escape_ascii(), "skipping non-letter");
+                stats.skipped += 1;
+                if policy == StepPolicy::StepOnAll {
+                    stats.total_steps += self.rotate();
+                }
+                if self.pass_through {
+                    output.push(b);
+                }
+                continue;
+            }
+
+            stats.letters += 1;
+            if self.step_order == StepOrder::Before {
+                stats.total_steps += self.rotate();
+            }
+            if self.core_state() != core_state {
+                core_state = self.core_state();
+                core = self.core_table();
+            }
+
+            let position = self.right_rotor.position().value();
+            let l = core[inward[position][w as usize] as usize];
+            output.push(outward[position][l as usize] + b'A');
+            trace!(
+                input = %wire_to_char(w),
+                output = %wire_to_char(outward[position][l as usize]),
+                "enciphered letter"
+            );
+
+            if self.step_order == StepOrder::After {
+                stats.total_steps += self.rotate();
+            }
+        }
+        output
+    }
+
+    /// The paths in through the plugboard, entry wheel and right rotor, and back out, for each
+    /// position of the right rotor.
+    fn right_tables(&self) -> (PositionTables, PositionTables) {
+        let mut inward = [[0; ALPHABET_SIZE]; ALPHABET_SIZE];
+        let mut outward = [[0; ALPHABET_SIZE]; ALPHABET_SIZE];
+        let mut right_rotor = self.right_rotor.clone();
+        for (position, (inward, outward)) in inward.iter_mut().zip(&mut outward).enumerate() {
+            right_rotor.set_position(position);
+            let right_table = right_rotor.forward_table();
+            for w in 0..ALPHABET_SIZE as u8 {
+                let mut l = self.plugboard.forward(w);
+                if let Some(entry_wheel) = &self.entry_wheel {
+                    l = entry_wheel.backward(l);
+                }
+                let l = right_table[l as usize];
+                inward[w as usize] = l;
+                // The path back out is the inverse of the path in.
+                outward[l as usize] = w;
+            }
+        }
+        (inward, outward)
+    }
+
+    /// The positions that [`Machine::core_table`] depends on.
+    fn core_state(&self) -> (ClockInt, ClockInt, ClockInt) {
+        (
+            self.middle_rotor.position(),
+            self.left_rotor.position(),
+            self.reflector.position(),
+        )
+    }
+
+    /// The path from the right rotor, through the reflector and back, in the current positions.
+    fn core_table(&self) -> [u8; ALPHABET_SIZE] {
+        let mut table = [0; ALPHABET_SIZE];
+        for (w, t) in table.iter_mut().enumerate() {
+            let l = self.middle_rotor.forward(w as u8);
+            let mut l = self.left_rotor.forward(l);
+            if let Some(greek_rotor) = &self.greek_rotor {
+                l = greek_rotor.forward(l);
+                l = self.reflector.forward(l);
+                l = greek_rotor.backward(l);
+            } else {
+                l = self.reflector.forward(l);
+            }
+            let l = self.left_rotor.backward(l);
+            *t = self.middle_rotor.backward(l);
+        }
+        table
+    }
+
+    /// Encrypts a `message` using the machine, choosing whether characters that aren't ASCII
+    /// letters step the rotors. They are left out of the output unless
+    /// [`Machine::set_pass_through`] is on.
+    pub fn encrypt_with_policy(&mut self, message: &str, policy: StepPolicy) -> String {
+        self.encrypt_message(message, policy).0
+    }
+
+    /// Encrypts a `message` using the machine, also returning counts of the letters enciphered, the
+    /// characters skipped and the rotor steps taken.
+    pub fn encrypt_with_stats(&mut self, message: &str) -> (String, EncryptStats) {
+        self.encrypt_message(message, StepPolicy::SkipNonLetters)
+    }
+
+    fn encrypt_message(&mut self, message: &str, policy: StepPolicy) -> (String, EncryptStats) {
+        let mut stats = EncryptStats::default();
+        let encrypted = message
             .chars()
             .flat_map(|c| {
                 if !c.is_ascii_alphabetic() {
-                    eprintln!("Skipping char: {}", c);
-                    return None;
+                    trace!(char = ?c, "skipping non-letter");
+                    stats.skipped += 1;
+                    if policy == StepPolicy::StepOnAll {
+                        stats.total_steps += self.rotate();
+                    }
+                    return self.pass_through.then_some(c);
                 }
 
-                self.rotate();
-
-                let l = char_to_wire(c);
-                let l = self.plugboard.forward(l);
-                let l = self.right_rotor.forward(l);
-                let l = self.middle_rotor.forward(l);
-                let l = self.left_rotor.forward(l);
-                let l = self.reflector.forward(l);
-                let l = self.left_rotor.backward(l);
-                let l = self.middle_rotor.backward(l);
-                let l = self.right_rotor.backward(l);
-                let l = self.plugboard.forward(l);
+                stats.letters += 1;
+                let (l, steps) = self.step_and_encipher(char_to_wire(c));
+                stats.total_steps += steps;
                 Some(wire_to_char(l))
             })
-            .collect()
+            .collect();
+
+        (encrypted, stats)
+    }
+
+    /// Steps the rotors and enciphers `letter` in the configured [`StepOrder`], returning the
+    /// enciphered letter and how many rotors moved.
+    fn step_and_encipher(&mut self, letter: u8) -> (u8, usize) {
+        let (l, steps) = match self.step_order {
+            StepOrder::Before => {
+                let steps = self.rotate();
+                (self.encipher_letter(letter), steps)
+            }
+            StepOrder::After => {
+                let l = self.encipher_letter(letter);
+                (l, self.rotate())
+            }
+        };
+        trace!(
+            input = %wire_to_char(letter),
+            output = %wire_to_char(l),
+            "enciphered letter"
+        );
+        (l, steps)
+    }
+
+    /// Passes `letter` through the machine in its current state, without stepping.
+    fn encipher_letter(&self, letter: u8) -> u8 {
+        let l = self.encipher_rotors(self.plugboard.forward(letter));
+        self.plugboard.backward(l)
+    }
+
+    /// Passes `letter` through everything between the plugboard and the reflector and back, in
+    /// the current state, without stepping.
+    fn encipher_rotors(&self, letter: u8) -> u8 {
+        let mut l = letter;
+        if let Some(entry_wheel) = &self.entry_wheel {
+            l = entry_wheel.backward(l);
+        }
+        let l = self.right_rotor.forward(l);
+        let l = self.middle_rotor.forward(l);
+        let mut l = self.left_rotor.forward(l);
+        if let Some(greek_rotor) = &self.greek_rotor {
+            l = greek_rotor.forward(l);
+            l = self.reflector.forward(l);
+            l = greek_rotor.backward(l);
+        } else {
+            l = self.reflector.forward(l);
+        }
+        let l = self.left_rotor.backward(l);
+        let l = self.middle_rotor.backward(l);
+        let mut l = self.right_rotor.backward(l);
+        if let Some(entry_wheel) = &self.entry_wheel {
+            l = entry_wheel.forward(l);
+        }
+        l
+    }
+}
+
+/// Enciphers a single wire value, stepping the rotors first unless the machine's [`StepOrder`] is
+/// [`StepOrder::After`].
+///
+/// This is the same transformation [`Machine::encrypt`] applies to each letter of a message.
+impl SteppingComponent for Machine {
+    fn encipher(&mut self, letter: u8) -> u8 {
+        self.step_and_encipher(letter).0
+    }
+}
+
+/// Parses a key string into a machine, as [`MachineConfig`]'s [`FromStr`] implementation does,
+/// e.g. `"I,II,III/A,A,A/M,C,K/B/AB CD"`.
+///
+/// # Examples
+///
+/// ```
+/// use enigma::Machine;
+/// let mut machine: Machine = "I,II,III/B,B,B/A,A,A/B".parse().unwrap();
+/// assert_eq!(machine.encrypt("EXAMPLEXMESSAGE"), "AQTSWCKHEVCFYBS");
+/// ```
+impl FromStr for Machine {
+    type Err = InvalidArgsError;
+
+    fn from_str(key: &str) -> Result<Self, Self::Err> {
+        key.parse::<MachineConfig>()?.build()
     }
 }
 
+/// Parses three letters as rotor positions.
+pub(crate) fn parse_positions(positions: &str) -> Option<(ClockInt, ClockInt, ClockInt)> {
+    match positions.as_bytes() {
+        &[left, middle, right] if positions.bytes().all(|b| b.is_ascii_alphabetic()) => Some((
+            ClockInt::from(char_to_wire(left as char)),
+            ClockInt::from(char_to_wire(middle as char)),
+            ClockInt::from(char_to_wire(right as char)),
+        )),
+        _ => None,
+    }
+}
+
+/// The variant of the machine being simulated, which decides how the rotors step and which
+/// rotors and reflectors it takes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EnigmaModel {
+    /// The 3-rotor Enigma I and M3 used by the Wehrmacht and Kriegsmarine, and the Norway Enigma.
+    /// The rotors are stepped by pawls and ratchets, which makes the middle rotor double step.
+    #[default]
+    M3,
+    /// The 4-rotor Kriegsmarine M4. Steps like the [`EnigmaModel::M3`], with a thin Greek rotor
+    /// that never steps between the left rotor and a thin reflector.
+    M4,
+    /// The Abwehr's Enigma G (Zählwerk). The rotors are driven by gears like an odometer, so
+    /// there's no double step, and the reflector steps as well when the left rotor turns over.
+    /// The keys are wired to the rotors through a QWERTZ entry wheel, see
+    /// [`ENTRY_WHEEL_QWERTZ`](wirings::ENTRY_WHEEL_QWERTZ).
+    G,
+    /// The railway Enigma, an Enigma K rewired for the Reichsbahn. Steps like the
+    /// [`EnigmaModel::M3`] and has the G's QWERTZ entry wheel, but no plugboard. Its reflector can
+    /// be set with [`Machine::set_reflector_position`] but doesn't step.
+    Railway,
+}
+
+impl EnigmaModel {
+    /// Number of rotors, including the M4's Greek rotor.
+    pub fn rotor_count(self) -> usize {
+        match self {
+            Self::M4 => 4,
+            Self::M3 | Self::G | Self::Railway => 3,
+        }
+    }
+
+    /// Whether the reflector steps as letters are typed.
+    pub fn has_stepping_reflector(self) -> bool {
+        self == Self::G
+    }
+
+    /// Families of the rotors that fit the stepping rotor slots. The M4's Greek rotor slot only
+    /// takes the [`RotorFamily::Greek`] rotors. The stepping rotors of one machine must all come
+    /// from the same family, e.g. the M3 takes either the Wehrmacht or the Norway rotors.
+    pub fn rotor_families(self) -> &'static [RotorFamily] {
+        match self {
+            Self::M3 => &[RotorFamily::Wehrmacht, RotorFamily::Norway],
+            Self::M4 => &[RotorFamily::Wehrmacht],
+            Self::G => &[RotorFamily::G],
+            Self::Railway => &[RotorFamily::Railway],
+        }
+    }
+
+    /// Names of the reflectors the model takes.
+    pub fn reflectors(self) -> &'static [&'static str] {
+        match self {
+            Self::M3 => &["A", "B", "C", "D", "NORWAY"],
+            Self::M4 => &["B-THIN", "C-THIN"],
+            Self::G => &["G"],
+            Self::Railway => &["RAILWAY"],
+        }
+    }
+
+    /// The entry wheel, if it doesn't connect each key to the contact of the same letter.
+    fn entry_wheel(self) -> Option<Rotor> {
+        match self {
+            Self::M3 | Self::M4 => None,
+            Self::G | Self::Railway => {
+                Some(Rotor::new(wirings::ENTRY_WHEEL_QWERTZ, 0, 0, Vec::new()))
+            }
+        }
+    }
+}
+
+/// Whether the middle rotor double steps on the models with pawl and ratchet stepping, i.e. every
+/// model but the [`EnigmaModel::G`].
+///
+/// On the real machine the pawl that turns the left rotor also pushes the middle rotor, so when
+/// the middle rotor reaches its notch it steps again on the next letter, along with the left
+/// rotor. Some simulators leave this out and step the rotors like an odometer, so their output
+/// differs from the real machine whenever the middle rotor reaches its notch.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DoubleStepMode {
+    /// Double step like the real machine.
+    #[default]
+    Historical,
+    /// Step like an odometer, for comparing with simulators that do.
+    None,
+}
+
+/// When the rotors step relative to enciphering each letter.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum StepOrder {
+    /// Step, then encipher. This is how the real machine behaves.
+    #[default]
+    Before,
+    /// Encipher, then step. Some emulators behave like this.
+    After,
+}
+
+/// Whether characters that can't be enciphered still step the rotors.
+///
+/// Implementations differ here: treating such characters as spaces typed on the keyboard steps the
+/// rotors, while dropping them before they reach the machine doesn't.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StepPolicy {
+    /// Only letters step the rotors. Used by [`Machine::encrypt`].
+    #[default]
+    SkipNonLetters,
+    /// Every character steps the rotors.
+    StepOnAll,
+}
+
+/// Counts gathered while encrypting a message with [`Machine::encrypt_with_stats`],
+/// [`Machine::process_reader_with_stats`] or [`Machine::process_reader_with_policy`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EncryptStats {
+    /// Number of letters enciphered.
+    pub letters: usize,
+    /// Number of characters skipped, or passed through unchanged, because they aren't ASCII
+    /// letters.
+    pub skipped: usize,
+    /// Total number of times any rotor moved, so a double step counts as three.
+    pub total_steps: usize,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{available_reflectors, available_rotors};
+    use proptest::prelude::*;
 
     macro_rules! test_encryption {
         ($input:literal, $expect:literal) => {
@@ -116,4 +1527,960 @@ mod tests {
             "PEKGUOMYWIMRREKEVQUTKUYHPEUNARUKIAHIMFOKUTWCWYDITIKPPTQKWDJIGHRYLWDSCIPXOGYXVJPSZOAJRAWTRRFXCLHSKYHSNVLVMTNVBSZEBOHUWSQJDEOFBNKKISVBYKQJSZZRYDGCJHVNPDGNRPBDRKUQBLPWZNVCMGFBUCFTNYGROTUVPJUDECYMJKEHWNCKULMLNEFEBXAAZABEGLTDJFMJFSKXTLIOWWZOMZONONVXVIISACDUACYVQRWUDKKGMSYEKBOGCDBUOSJBCJWKNKFETOIPYDVKWLDIXLLWQDPBTSY"
         );
     }
+
+    #[test]
+    fn test_step_before_substitution() {
+        // Rings and positions at A with reflector B is the well known AAAAA -> BDZGO setting.
+        let mut machine = Machine::new(("I", "II", "III"), (0, 0, 0), (0, 0, 0), "B", "");
+        assert_eq!(machine.encrypt("A"), "B");
+        assert_eq!(machine.positions(), parse_positions("AAB").unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "letter 'A' is connected more than once")]
+    fn test_new_rejects_duplicate_plug() {
+        Machine::new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "B", "AB AC");
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert_eq!(
+            Machine::try_new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "B", "AB CD"),
+            Ok(Machine::new(
+                ("I", "II", "III"),
+                (1, 1, 1),
+                (0, 0, 0),
+                "B",
+                "AB CD"
+            ))
+        );
+        assert_eq!(
+            Machine::try_new(("I", "IX", "III"), (1, 1, 1), (0, 0, 0), "B", ""),
+            Err(InvalidArgsError::InvalidRotorName("IX".to_string()))
+        );
+        assert_eq!(
+            Machine::try_new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "E", ""),
+            Err(InvalidArgsError::InvalidReflectorName("E".to_string()))
+        );
+        assert_eq!(
+            Machine::try_new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "B", "AB AC"),
+            Err(InvalidArgsError::DuplicatePlug('A'))
+        );
+
+        // Rotors and reflectors are checked against the M3 like MachineConfig::build does.
+        assert_eq!(
+            Machine::try_new(("BETA", "G-I", "III"), (0, 0, 0), (0, 0, 0), "B", ""),
+            Err(InvalidArgsError::NotInModel("BETA".to_string()))
+        );
+        assert_eq!(
+            Machine::try_new(("I", "II", "III"), (0, 0, 0), (0, 0, 0), "B-THIN", ""),
+            Err(InvalidArgsError::NotInModel("B-THIN".to_string()))
+        );
+        assert_eq!(
+            Machine::try_new(("I", "NORWAY-II", "III"), (0, 0, 0), (0, 0, 0), "B", ""),
+            Err(InvalidArgsError::MixedRotorFamilies(
+                "NORWAY-II".to_string()
+            ))
+        );
+        assert_eq!(
+            Machine::with_rotor_names(
+                (RotorName::GI, RotorName::GII, RotorName::GIII),
+                (0, 0, 0),
+                (0, 0, 0),
+                "G",
+                ""
+            ),
+            Err(InvalidArgsError::NotInModel("G-I".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_new_panics_on_invalid_connections() {
+        for connections in ["AB AC", "AB CDE", "AB C1"] {
+            let result = std::panic::catch_unwind(|| {
+                Machine::new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "B", connections)
+            });
+            assert!(result.is_err(), "{}", connections);
+        }
+    }
+
+    #[test]
+    fn test_validate_config() {
+        assert_eq!(
+            Machine::validate_config(("I", "II", "III"), (0, 0, 25), (25, 0, 0), "B", "AB CD"),
+            Ok(())
+        );
+        assert_eq!(
+            Machine::validate_config(("I", "II", "I"), (0, 26, 0), (0, 0, 40), "E", "AB1 AC CA"),
+            Err(vec![
+                InvalidArgsError::RepeatedRotor("I".to_string()),
+                InvalidArgsError::SettingOutOfRange {
+                    setting: "ring setting",
+                    value: 26
+                },
+                InvalidArgsError::SettingOutOfRange {
+                    setting: "rotor position",
+                    value: 40
+                },
+                InvalidArgsError::InvalidReflectorName("E".to_string()),
+                InvalidArgsError::InvalidPlugboard("AB1".to_string()),
+                InvalidArgsError::DuplicatePlug('C'),
+                InvalidArgsError::DuplicatePlug('A'),
+            ])
+        );
+
+        // The model checks are the ones MachineConfig::build and Machine::try_new use.
+        let rotors = ("BETA", "G-I", "III");
+        assert_eq!(
+            Machine::validate_config(rotors, (0, 0, 0), (0, 0, 0), "G", ""),
+            Err(vec![
+                InvalidArgsError::NotInModel("BETA".to_string()),
+                InvalidArgsError::NotInModel("G-I".to_string()),
+                InvalidArgsError::NotInModel("G".to_string()),
+            ])
+        );
+        assert!(Machine::try_new(rotors, (0, 0, 0), (0, 0, 0), "G", "").is_err());
+
+        let rotors = ("NORWAY-I", "II", "NORWAY-III");
+        assert_eq!(
+            Machine::validate_config(rotors, (0, 0, 0), (0, 0, 0), "NORWAY", ""),
+            Err(vec![InvalidArgsError::MixedRotorFamilies("II".to_string())])
+        );
+        assert_eq!(
+            Machine::try_new(rotors, (0, 0, 0), (0, 0, 0), "NORWAY", ""),
+            Err(InvalidArgsError::MixedRotorFamilies("II".to_string()))
+        );
+
+        // Only the dry run checks for repeated rotors.
+        let rotors = ("I", "I", "III");
+        assert_eq!(
+            Machine::validate_config(rotors, (0, 0, 0), (0, 0, 0), "B", ""),
+            Err(vec![InvalidArgsError::RepeatedRotor("I".to_string())])
+        );
+        assert!(Machine::try_new(rotors, (0, 0, 0), (0, 0, 0), "B", "").is_ok());
+        assert!(MachineConfig::default()
+            .rotors(&["I", "I", "III"])
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_settings_reduced() {
+        let machine = Machine::new(("I", "II", "III"), (27, 1, 52), (30, 0, 25), "B", "");
+        assert_eq!(machine.ring_settings(), parse_positions("BBA").unwrap());
+        assert_eq!(machine.positions(), parse_positions("EAZ").unwrap());
+
+        let mut wrapped = machine.clone();
+        let mut reduced = Machine::new(("I", "II", "III"), (1, 1, 0), (4, 0, 25), "B", "");
+        assert_eq!(wrapped, reduced);
+        assert_eq!(
+            wrapped.encrypt("HELLOXWORLD"),
+            reduced.encrypt("HELLOXWORLD")
+        );
+    }
+
+    #[test]
+    fn test_clone_eq() {
+        let mut machine = Machine::new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "B", "AB");
+        let snapshot = machine.clone();
+        assert_eq!(snapshot, machine);
+
+        machine.encrypt("A");
+        assert_ne!(snapshot, machine);
+
+        let other = Machine::new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "B", "AC");
+        assert_ne!(snapshot, other);
+        let other = Machine::new(("I", "II", "IV"), (1, 1, 1), (0, 0, 0), "B", "AB");
+        assert_ne!(snapshot, other);
+        let other = Machine::new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "C", "AB");
+        assert_ne!(snapshot, other);
+    }
+
+    #[test]
+    fn test_process_reader() {
+        let message = "HELLOXWORLD".repeat(1000);
+        let mut machine = Machine::new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "B", "");
+        let mut expected = machine.clone();
+
+        let mut output = Vec::new();
+        let stats = machine
+            .process_reader_with_stats(message.as_bytes(), &mut output)
+            .unwrap();
+        assert_eq!(output, expected.encrypt(&message).into_bytes());
+        assert_eq!(stats.letters, message.len());
+        assert_eq!(stats.skipped, 0);
+        assert_eq!(machine, expected);
+
+        let mut machine = Machine::new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "B", "");
+        machine.set_pass_through(true);
+        let mut output = Vec::new();
+        machine
+            .process_reader("HELLO, X\nWORLD!\n".as_bytes(), &mut output)
+            .unwrap();
+        assert_eq!(output, b"LOFUH, Z\nZLZOM!\n");
+
+        let mut machine = Machine::new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "B", "");
+        let mut expected = machine.clone();
+        let mut output = Vec::new();
+        machine
+            .process_reader_with_policy(
+                "HELLO, X\nWORLD!\n".as_bytes(),
+                &mut output,
+                StepPolicy::StepOnAll,
+            )
+            .unwrap();
+        let encrypted = expected.encrypt_with_policy("HELLO, X\nWORLD!\n", StepPolicy::StepOnAll);
+        assert_eq!(output, encrypted.into_bytes());
+        assert_eq!(machine, expected);
+    }
+
+    #[test]
+    fn test_canonical_key() {
+        let mut a = Machine::new(("I", "II", "III"), (1, 2, 3), (4, 5, 6), "B", "AB");
+        let mut b = Machine::new(("I", "II", "III"), (11, 2, 3), (14, 5, 6), "B", "AB");
+        let c = Machine::new(("I", "II", "III"), (1, 2, 3), (5, 5, 6), "B", "AB");
+        let d = Machine::new(("I", "II", "III"), (1, 3, 3), (4, 6, 6), "B", "AB");
+        assert!(a.canonical_key() == b.canonical_key());
+        assert!(a.canonical_key() != c.canonical_key());
+        assert!(a.canonical_key() != d.canonical_key());
+
+        let message = "A".repeat(1000);
+        assert_eq!(a.encrypt(&message), b.encrypt(&message));
+        assert!(a.canonical_key() == b.canonical_key());
+
+        let keys: std::collections::HashSet<_> =
+            [&a, &b, &c].iter().map(|m| m.canonical_key()).collect();
+        assert_eq!(keys.len(), 2);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut machine = Machine::new(("I", "II", "III"), (0, 0, 0), (0, 3, 20), "B", "");
+        let start = machine.clone();
+        let encrypted = machine.encrypt("HELLOXWORLD");
+        machine.reset();
+        assert_eq!(machine, start);
+        assert_eq!(machine.encrypt("HELLOXWORLD"), encrypted);
+
+        machine.set_positions(parse_positions("ZZZ").unwrap());
+        machine.encrypt("HELLO");
+        machine.reset();
+        assert_eq!(machine.positions(), clock_ints((25, 25, 25)));
+    }
+
+    #[test]
+    fn test_positions_after() {
+        for model in [
+            EnigmaModel::M3,
+            EnigmaModel::M4,
+            EnigmaModel::G,
+            EnigmaModel::Railway,
+        ] {
+            let mut machine = MachineConfig::new(model).build().unwrap();
+            machine.set_positions(parse_positions("QDU").unwrap());
+            assert_eq!(machine.positions_after(0), machine.positions());
+            for n in [1, 2, 3, 25, 26, 700, 20000] {
+                let mut stepped = machine.clone();
+                stepped.encrypt(&"A".repeat(n));
+                assert_eq!(machine.positions_after(n), stepped.positions());
+            }
+        }
+    }
+
+    #[test]
+    fn test_keypresses_to_left_step() {
+        for model in [
+            EnigmaModel::M3,
+            EnigmaModel::M4,
+            EnigmaModel::G,
+            EnigmaModel::Railway,
+        ] {
+            let mut machine = MachineConfig::new(model).build().unwrap();
+            for positions in ["AAA", "QDU", "ZEV"] {
+                machine.set_positions(parse_positions(positions).unwrap());
+                let n = machine.keypresses_to_left_step().unwrap();
+                assert_eq!(machine.positions_after(n - 1).0, machine.positions().0);
+                assert_ne!(machine.positions_after(n).0, machine.positions().0);
+            }
+        }
+
+        // Rotor II double steps at E, so from AEV the first press steps the left rotor.
+        let mut machine = Machine::default_m3();
+        machine.set_positions(parse_positions("AEV").unwrap());
+        assert_eq!(machine.keypresses_to_left_step(), Some(1));
+
+        let machine = MachineConfig::default()
+            .notches(&[vec![0], vec![], vec![0]])
+            .build()
+            .unwrap();
+        assert_eq!(machine.keypresses_to_left_step(), None);
+    }
+
+    #[test]
+    fn test_fast_forward() {
+        let message = "THEXQUICKXBROWNXFOXXJUMPSXOVERXTHEXLAZYXDOG".repeat(20);
+        for model in [
+            EnigmaModel::M3,
+            EnigmaModel::M4,
+            EnigmaModel::G,
+            EnigmaModel::Railway,
+        ] {
+            let config = MachineConfig::new(model);
+            let expected = config.build().unwrap().encrypt(&message);
+            for n in [0, 1, 25, 26, 300, message.len()] {
+                let mut machine = config.build().unwrap();
+                machine.fast_forward(n);
+                assert_eq!(machine.encrypt(&message[n..]), expected[n..]);
+            }
+        }
+
+        let mut machine = Machine::default_m3();
+        machine.set_history(true);
+        machine.fast_forward(3);
+        assert_eq!(machine.positions(), clock_ints((0, 0, 3)));
+        assert!(machine.undo());
+        assert_eq!(machine.positions(), clock_ints((0, 0, 2)));
+        machine.reset();
+        assert_eq!(machine.positions(), clock_ints((0, 0, 0)));
+    }
+
+    #[test]
+    fn test_settings_string() {
+        assert_eq!(
+            Machine::default_m3().settings_string(),
+            "I,II,III/A,A,A/A,A,A/B/"
+        );
+
+        let machine: Machine = "BETA,VI,II,VIII/A,B,C,D/W,X,Y,Z/B-THIN/ZA QB"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            machine.settings_string(),
+            "BETA,VI,II,VIII/A,B,C,D/W,X,Y,Z/B-THIN/AZ BQ"
+        );
+        assert_eq!(machine.settings_string().parse::<Machine>(), Ok(machine));
+
+        let machine = MachineConfig::new(EnigmaModel::G).build().unwrap();
+        assert_eq!(machine.settings_string(), "G-I,G-II,G-III/A,A,A/A,A,A,A/G/");
+    }
+
+    #[test]
+    fn test_settings_checksum() {
+        let machine = Machine::default_m3();
+        let mut other = machine.clone();
+        assert_eq!(machine.settings_checksum(), other.settings_checksum());
+
+        // Transposed or changed letters change the checksum.
+        other.set_positions(parse_positions("AAB").unwrap());
+        assert_ne!(machine.settings_checksum(), other.settings_checksum());
+        let swapped = Machine::new(("II", "I", "III"), (0, 0, 0), (0, 0, 0), "B", "");
+        assert_ne!(machine.settings_checksum(), swapped.settings_checksum());
+    }
+
+    #[test]
+    fn test_set_plugboard() {
+        let settings = (("I", "II", "III"), (0, 0, 0), (0, 0, 0), "B");
+        let with_plugs = |connections| {
+            let (rotors, rings, positions, reflector) = settings;
+            Machine::new(rotors, rings, positions, reflector, connections)
+        };
+
+        let mut machine = with_plugs("");
+        machine.set_plugboard("AB CD EF").unwrap();
+        assert_eq!(machine, with_plugs("AB CD EF"));
+        assert_eq!(
+            machine.set_plugboard("AB CA"),
+            Err(InvalidArgsError::DuplicatePlug('A'))
+        );
+        assert_eq!(machine, with_plugs("AB CD EF"));
+
+        machine.clear_plugboard();
+        assert_eq!(machine, with_plugs(""));
+        assert_eq!(machine.encrypt("AAAAA"), "BDZGO");
+    }
+
+    #[test]
+    fn test_set_reflector() {
+        let mut machine = Machine::default_m3();
+        machine.encrypt("HELLO");
+        let positions = machine.positions();
+
+        machine.set_reflector("C").unwrap();
+        assert_eq!(machine.reflector_name(), "C");
+        assert_eq!(machine.positions(), positions);
+        let mut expected = Machine::new(("I", "II", "III"), (0, 0, 0), (0, 0, 0), "C", "");
+        expected.set_positions(positions);
+        assert_eq!(machine.encrypt("WORLD"), expected.encrypt("WORLD"));
+
+        assert_eq!(
+            machine.set_reflector("E"),
+            Err(InvalidArgsError::InvalidReflectorName("E".to_string()))
+        );
+        assert_eq!(
+            machine.set_reflector("G"),
+            Err(InvalidArgsError::NotInModel("G".to_string()))
+        );
+        assert_eq!(machine.reflector_name(), "C");
+
+        let mut machine = MachineConfig::new(EnigmaModel::M4).build().unwrap();
+        assert_eq!(machine.reflector_name(), "B-THIN");
+        machine.set_reflector("C-THIN").unwrap();
+        assert_eq!(machine.reflector_name(), "C-THIN");
+    }
+
+    #[test]
+    fn test_encrypt_lines() {
+        let mut machine = Machine::default_m3();
+        let encrypted = machine.encrypt_lines("HELLO\r\n\nWORLD, AGAIN\nX");
+        assert_eq!(encrypted.lines().count(), 4);
+        assert_eq!(
+            encrypted.replace(['\r', '\n'], ""),
+            Machine::default_m3().encrypt("HELLOWORLDAGAINX")
+        );
+        assert!(encrypted.starts_with("ILBDA\r\n\n"));
+
+        let (_, stats) =
+            Machine::default_m3().encrypt_lines_with_stats("HELLO\r\n\nWORLD, AGAIN\nX");
+        assert_eq!(stats.letters, 16);
+        assert_eq!(stats.skipped, 2);
+
+        // Passed-through characters don't duplicate the line endings.
+        let mut machine = Machine::default_m3();
+        machine.set_pass_through(true);
+        assert_eq!(machine.encrypt_lines("A A\nA\n"), "B D\nZ\n");
+    }
+
+    #[test]
+    fn test_encrypt_pairs() {
+        let message = "Hello, world";
+        let pairs = Machine::default_m3().encrypt_pairs(message);
+        let (input, output): (String, String) = pairs.into_iter().unzip();
+        assert_eq!(input, "Helloworld");
+        assert_eq!(output, Machine::default_m3().encrypt(message));
+
+        let mut machine = Machine::default_m3();
+        machine.set_pass_through(true);
+        let pairs = machine.encrypt_pairs(message);
+        assert_eq!(pairs.len(), message.len());
+        assert_eq!(pairs[5], (',', ','));
+        assert_eq!(pairs[6], (' ', ' '));
+    }
+
+    #[test]
+    fn test_undo() {
+        let mut machine = Machine::new(("I", "II", "III"), (0, 0, 0), (0, 3, 20), "B", "");
+        let start = machine.clone();
+        assert_eq!(machine.encrypt_char('A'), Some('E'));
+        assert!(!machine.undo());
+
+        let mut machine = start.clone();
+        machine.set_history(true);
+        let encrypted: String = "AAAAA"
+            .chars()
+            .filter_map(|c| machine.encrypt_char(c))
+            .collect();
+        assert_eq!(machine.encrypt_char('!'), None);
+        for _ in 0..3 {
+            assert!(machine.undo());
+        }
+        let mut expected = start.clone();
+        expected.encrypt("AA");
+        assert_eq!(machine.positions(), expected.positions());
+        assert_eq!(machine.encrypt_char('A'), encrypted.chars().nth(2));
+
+        machine.clear_history();
+        assert!(!machine.undo());
+        machine.encrypt("AA");
+        assert!(machine.undo() && machine.undo());
+        assert!(!machine.undo());
+    }
+
+    #[test]
+    fn test_set_positions() {
+        let mut machine = Machine::new(("I", "II", "III"), (0, 0, 0), (0, 0, 0), "B", "");
+        machine.set_positions(parse_positions("ADU").unwrap());
+        assert_eq!(
+            machine,
+            Machine::new(("I", "II", "III"), (0, 0, 0), (0, 3, 20), "B", "")
+        );
+        machine.set_positions((27usize, 0, 0));
+        assert_eq!(machine.positions().0, ClockInt::new(1));
+    }
+
+    #[test]
+    fn test_indicator_procedure() {
+        // The example message of the 1930 instruction manual: rotors II I III, ring settings
+        // 24 13 22, reflector A, Grundstellung FOL and message key ABL.
+        let mut machine = Machine::new(
+            ("II", "I", "III"),
+            (23, 12, 21),
+            (0, 0, 0),
+            "A",
+            "AM FI NV PS TU WZ",
+        );
+        let body = "FEINDLIQEINFANTERIEKOLONNEBEOBAQTETXANFANGSUEDAUSGANGBAERWALDEX\
+                    ENDEDREIKMOSTWAERTSNEUSTADT";
+        let encrypted = "PKPJXIGCDSEAHUGWTQGRKVLFGXUCALXVYMIGMMNMFDXTGNVHVRMMEVOUYFZSLRH\
+                         DRRXFJWCFHUHMUNZEFRDISIKBGPMYVXUZ";
+        assert_eq!(
+            machine.encrypt_with_indicator_procedure("FOL", "ABL", body),
+            Ok(encrypted.to_string())
+        );
+
+        assert_eq!(
+            machine.encrypt_with_indicator_procedure("FOL", "AB1", body),
+            Err(InvalidArgsError::InvalidKey(
+                "message key must be three letters, found 'AB1'".to_string()
+            ))
+        );
+        assert_eq!(
+            machine.encrypt_with_indicator_procedure("FO", "ABL", body),
+            Err(InvalidArgsError::InvalidKey(
+                "Grundstellung must be three letters, found 'FO'".to_string()
+            ))
+        );
+    }
+
+    fn clock_ints(values: (usize, usize, usize)) -> (ClockInt, ClockInt, ClockInt) {
+        (values.0.into(), values.1.into(), values.2.into())
+    }
+
+    #[test]
+    fn test_model_g() {
+        let new_g =
+            |positions| Machine::new_g(("G-I", "G-II", "G-III"), (0, 0, 0), positions, 0).unwrap();
+        assert_eq!(new_g((0, 0, 0)).model(), EnigmaModel::G);
+
+        let mut machine = new_g((0, 0, 0));
+        let encrypted = machine.encrypt("HELLOXWORLD");
+        assert_eq!(encrypted, "IQKSUUOXIGY");
+        assert_eq!(new_g((0, 0, 0)).encrypt(&encrypted), "HELLOXWORLD");
+
+        // No double step: the middle rotor at a notch waits for the right rotor's notch.
+        let mut machine = new_g((0, 0, 1));
+        machine.encrypt("A");
+        assert_eq!(machine.positions(), clock_ints((0, 0, 2)));
+
+        // With every rotor at a notch the whole train steps, including the reflector.
+        let mut machine = new_g((0, 0, 0));
+        machine.set_history(true);
+        let steps = machine.encrypt_with_stats("A").1.total_steps;
+        assert_eq!(steps, 4);
+        assert_eq!(machine.positions(), clock_ints((1, 1, 1)));
+        assert_eq!(machine.reflector_position(), ClockInt::new(1));
+        assert!(machine.undo());
+        assert_eq!(machine.reflector_position(), ClockInt::new(0));
+
+        assert!(new_g((0, 0, 0)).canonical_key() != new_g((1, 0, 0)).canonical_key());
+
+        // Starting with the reflector turned changes the output.
+        let mut turned = Machine::new_g(("G-I", "G-II", "G-III"), (0, 0, 0), (0, 0, 0), 1).unwrap();
+        assert_eq!(turned.reflector_position(), ClockInt::new(1));
+        assert!(turned.encrypt("HELLOXWORLD") != encrypted);
+
+        // Not a published answer: a regression value, checked letter by letter against a separate
+        // implementation written from the G-312 wiring tables.
+        let mut machine =
+            Machine::new_g(("G-III", "G-I", "G-II"), (5, 17, 2), (11, 3, 24), 7).unwrap();
+        assert_eq!(
+            machine.encrypt("ANGRIFFXUMXVIERXUHRXBEIXMORGENGRAUEN"),
+            "HKUXJDWBJOMCMKJESMIRDABLQJPUZWMOEECG"
+        );
+    }
+
+    #[test]
+    fn test_model_railway() {
+        let rotors = ("RAILWAY-I", "RAILWAY-II", "RAILWAY-III");
+        let new_railway = |positions| {
+            Machine::with_model(
+                EnigmaModel::Railway,
+                rotors,
+                (0, 0, 0),
+                positions,
+                "RAILWAY",
+                "",
+            )
+            .unwrap()
+        };
+        assert_eq!(new_railway((0, 0, 0)).model(), EnigmaModel::Railway);
+
+        let mut machine = new_railway((0, 0, 0));
+        let encrypted = machine.encrypt("HELLOXWORLD");
+        assert_eq!(new_railway((0, 0, 0)).encrypt(&encrypted), "HELLOXWORLD");
+
+        // The right rotor turns over from Y, and the middle rotor double steps from E.
+        let mut machine = new_railway((0, 0, 24));
+        machine.encrypt("A");
+        assert_eq!(machine.positions(), clock_ints((0, 1, 25)));
+        let mut machine = new_railway((0, 4, 0));
+        machine.encrypt("A");
+        assert_eq!(machine.positions(), clock_ints((1, 5, 1)));
+
+        // The reflector can be set, but stays put.
+        let mut turned = new_railway((0, 0, 0));
+        turned.set_reflector_position(ClockInt::new(1));
+        assert!(turned.encrypt("HELLOXWORLD") != encrypted);
+        assert_eq!(turned.reflector_position(), ClockInt::new(1));
+
+        // The keys are wired through the QWERTZ entry wheel, unlike on the M3.
+        let mut straight = Machine::from_components(
+            (
+                Rotor::railway_i(0, 0),
+                Rotor::railway_ii(0, 0),
+                Rotor::railway_iii(0, 0),
+            ),
+            Reflector::railway(),
+            Plugboard::default(),
+        );
+        assert!(straight.encrypt("HELLOXWORLD") != encrypted);
+
+        assert_eq!(
+            Machine::with_model(
+                EnigmaModel::Railway,
+                ("I", "II", "III"),
+                (0, 0, 0),
+                (0, 0, 0),
+                "B",
+                ""
+            ),
+            Err(InvalidArgsError::NotInModel("I".to_string()))
+        );
+        assert_eq!(
+            Machine::with_model(EnigmaModel::M3, rotors, (0, 0, 0), (0, 0, 0), "RAILWAY", ""),
+            Err(InvalidArgsError::NotInModel("RAILWAY-I".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_stepping_component() {
+        let mut machine = Machine::new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "B", "");
+        let encrypted: String = "HELLOXWORLD"
+            .chars()
+            .map(|c| wire_to_char(machine.encipher(char_to_wire(c))))
+            .collect();
+        assert_eq!(encrypted, "LOFUHZZLZOM");
+    }
+
+    #[test]
+    fn test_step_policy() {
+        let mut skip = Machine::new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "B", "");
+        let mut step = skip.clone();
+        assert_eq!(
+            skip.encrypt_with_policy("HELLO WORLD", StepPolicy::SkipNonLetters),
+            "LOFUHHMJJX"
+        );
+        assert_eq!(skip.positions(), parse_positions("AAK").unwrap());
+
+        // The space steps the rotors like the X in "HELLOXWORLD" does.
+        assert_eq!(
+            step.encrypt_with_policy("HELLO WORLD", StepPolicy::StepOnAll),
+            "LOFUHZLZOM"
+        );
+        assert_eq!(step.positions(), parse_positions("AAL").unwrap());
+    }
+
+    #[test]
+    fn test_step_order() {
+        let mut before = Machine::new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "B", "");
+        let mut after = before.clone();
+        after.set_step_order(StepOrder::After);
+
+        // Stepping after means each letter sees the state the previous letter saw when stepping
+        // before, so the outputs are offset by one character.
+        let before = before.encrypt("AAAAAA");
+        let after = after.encrypt("AAAAAA");
+        assert_eq!(before, "EWTYXQ");
+        assert_eq!(&after[1..], &before[..5]);
+        assert_ne!(after, before);
+    }
+
+    #[test]
+    fn test_pass_through() {
+        let mut machine = Machine::new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "B", "");
+        machine.set_pass_through(true);
+        assert_eq!(machine.encrypt("HELLO, X WORLD!"), "LOFUH, Z ZLZOM!");
+    }
+
+    #[test]
+    fn test_ring_settings_from_zero() {
+        // Ring settings 01 01 01 and 02 02 02 on a key sheet, from AAA.
+        let mut machine = Machine::new(("I", "II", "III"), (0, 0, 0), (0, 0, 0), "B", "");
+        assert_eq!(machine.encrypt("AAAAA"), "BDZGO");
+        let mut machine = Machine::new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "B", "");
+        assert_eq!(machine.encrypt("AAAAA"), "EWTYX");
+    }
+
+    #[test]
+    fn test_encrypt_char_indexed() {
+        let mut machine = Machine::default_m3();
+        machine.set_positions(parse_positions("ADU").unwrap());
+        let mut expected = machine.clone();
+        for window in [('A', 'D', 'V'), ('A', 'E', 'W'), ('B', 'F', 'X')] {
+            let encrypted = expected.encrypt_char('Q');
+            assert_eq!(
+                machine.encrypt_char_indexed('Q'),
+                encrypted.map(|e| (e, window))
+            );
+        }
+
+        machine.set_step_order(StepOrder::After);
+        assert_eq!(
+            machine.encrypt_char_indexed('q').map(|(_, window)| window),
+            Some(('B', 'F', 'Y'))
+        );
+        assert_eq!(machine.encrypt_char_indexed('1'), None);
+    }
+
+    #[test]
+    fn test_encipher_components_only() {
+        let mut machine = MachineConfig::new(EnigmaModel::M4)
+            .plugboard("AV BS CG DL FU HZ IN KM OW RX")
+            .build()
+            .unwrap();
+        let mut unplugged = machine.clone();
+        unplugged.clear_plugboard();
+
+        assert_eq!(machine.encipher_plugboard_only('a'), 'V');
+        assert_eq!(machine.encipher_plugboard_only('E'), 'E');
+        for _ in 0..30 {
+            for c in 'A'..='Z' {
+                let through = machine.encipher_plugboard_only(
+                    machine.encipher_rotors_only(machine.encipher_plugboard_only(c)),
+                );
+                let mut stepping_after = machine.clone();
+                stepping_after.set_step_order(StepOrder::After);
+                assert_eq!(stepping_after.encrypt_char(c), Some(through));
+                assert_eq!(
+                    unplugged.encipher_rotors_only(c),
+                    machine.encipher_rotors_only(c)
+                );
+            }
+            machine.encrypt("A");
+            unplugged.encrypt("A");
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_encrypt_timed() {
+        use std::time::{Duration, Instant};
+
+        let message = "Hello, world!";
+        for pass_through in [false, true] {
+            let mut machine = Machine::default_m3();
+            machine.set_pass_through(pass_through);
+            let expected = machine.clone().encrypt(message);
+
+            let mut output = String::new();
+            let start = Instant::now();
+            machine.encrypt_timed(message, Duration::from_millis(2), |c| output.push(c));
+            assert_eq!(output, expected);
+            assert!(start.elapsed() >= Duration::from_millis(2) * expected.len() as u32);
+        }
+    }
+
+    #[test]
+    fn test_current_substitution() {
+        for model in [
+            EnigmaModel::M3,
+            EnigmaModel::M4,
+            EnigmaModel::G,
+            EnigmaModel::Railway,
+        ] {
+            let mut machine = MachineConfig::new(model)
+                .plugboard("AV BS CG DL FU HZ IN KM OW RX")
+                .build()
+                .unwrap();
+            for _ in 0..30 {
+                let before = machine.clone();
+                let substitution = machine.current_substitution();
+                assert_eq!(machine, before);
+
+                let mut stepping_after = machine.clone();
+                stepping_after.set_step_order(StepOrder::After);
+                for (c, s) in ('A'..='Z').zip(substitution.chars()) {
+                    assert_eq!(stepping_after.clone().encrypt_char(c), Some(s));
+                    assert_eq!(substitution.as_bytes()[char_to_wire(s) as usize], c as u8);
+                }
+                machine.encrypt("A");
+            }
+        }
+    }
+
+    #[test]
+    fn test_encrypt_with_digit_policy() {
+        let message = "Ankunft 1800 Uhr";
+        assert_eq!(
+            Machine::default_m3().encrypt_with_digit_policy(message, DigitPolicy::Skip),
+            Machine::default_m3().encrypt(message)
+        );
+
+        let policy = DigitPolicy::Replace(&text::TOP_ROW_DIGITS);
+        let encrypted = Machine::default_m3().encrypt_with_digit_policy(message, policy);
+        assert_eq!(encrypted, Machine::default_m3().encrypt("AnkunftQIPPUhr"));
+        assert_eq!(Machine::default_m3().encrypt(&encrypted), "ANKUNFTQIPPUHR");
+    }
+
+    #[test]
+    fn test_encrypt_cow() {
+        let mut machine = Machine::default_m3();
+        assert!(matches!(machine.encrypt_cow("1, 2!"), Cow::Borrowed("")));
+        machine.set_pass_through(true);
+        assert!(matches!(
+            machine.encrypt_cow("1, 2!"),
+            Cow::Borrowed("1, 2!")
+        ));
+        assert_eq!(machine.positions(), clock_ints((0, 0, 0)));
+
+        let expected = machine.clone().encrypt("HELLO, X WORLD!");
+        let encrypted = machine.encrypt_cow("HELLO, X WORLD!");
+        assert!(matches!(encrypted, Cow::Owned(_)));
+        assert_eq!(encrypted, expected);
+    }
+
+    #[test]
+    fn test_encrypt_normalized() {
+        let mut machine = Machine::new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "B", "");
+        let mut expected = machine.clone();
+        assert_eq!(
+            machine.encrypt_normalized("Hello. World"),
+            expected.encrypt("HELLOXWORLD")
+        );
+
+        let mut machine = Machine::new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "B", "");
+        assert_eq!(
+            machine.encrypt_normalized_with("Hello_World", &[('_', "X")]),
+            "LOFUHZZLZOM"
+        );
+    }
+
+    #[test]
+    fn test_encrypt_bytes() {
+        let message = "Hello, X world! 123\n";
+        for pass_through in [false, true] {
+            let mut machine = Machine::new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "B", "");
+            machine.set_pass_through(pass_through);
+            let mut expected = machine.clone();
+
+            assert_eq!(
+                machine.encrypt_bytes(message.as_bytes()),
+                expected.encrypt(message).into_bytes()
+            );
+            assert_eq!(machine, expected);
+        }
+    }
+
+    #[test]
+    fn test_encrypt_with_stats() {
+        let mut machine = Machine::new(("I", "II", "III"), (1, 1, 1), (0, 0, 0), "B", "");
+        let (encrypted, stats) = machine.encrypt_with_stats("HELLO, X WORLD!");
+        assert_eq!(encrypted, "LOFUHZZLZOM");
+        assert_eq!(
+            stats,
+            EncryptStats {
+                letters: 11,
+                skipped: 4,
+                total_steps: 11,
+            }
+        );
+
+        // The right rotor reaches its notch on the second letter, causing a double step next.
+        let mut machine = Machine::new(("I", "II", "III"), (0, 0, 0), (0, 3, 20), "B", "");
+        let (_, stats) = machine.encrypt_with_stats("AAA");
+        assert_eq!(stats.total_steps, 6);
+    }
+
+    fn plugboard() -> impl Strategy<Value = String> {
+        let letters: Vec<char> = ('A'..='Z').collect();
+        proptest::sample::subsequence(letters, 0..=26)
+            .prop_shuffle()
+            .prop_map(|letters| {
+                letters
+                    .chunks_exact(2)
+                    .map(|pair| pair.iter().collect::<String>())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+    }
+
+    proptest! {
+        // The left rotor never turns over another rotor on the M3, so moving its ring setting and
+        // position together gives the same machine, for any message.
+        #[test]
+        fn prop_left_ring_setting_only_shifts(
+            rings in (0..26usize, 0..26usize, 0..26usize),
+            positions in (0..26usize, 0..26usize, 0..26usize),
+            shift in 0..26usize,
+            message in "[A-Z]{0,1000}",
+        ) {
+            let rotors = ("I", "II", "III");
+            let mut machine = Machine::new(rotors, rings, positions, "B", "AB CD");
+            let mut shifted = Machine::new(
+                rotors,
+                (rings.0 + shift, rings.1, rings.2),
+                (positions.0 + shift, positions.1, positions.2),
+                "B",
+                "AB CD",
+            );
+            prop_assert_eq!(machine.encrypt(&message), shifted.encrypt(&message));
+        }
+
+        #[test]
+        fn prop_encrypt_bytes_matches_encrypt(
+            seed in any::<u64>(),
+            model in proptest::sample::select(
+                vec![EnigmaModel::M3, EnigmaModel::M4, EnigmaModel::G],
+            ),
+            positions in proptest::collection::vec(0..26usize, 4),
+            step_order in proptest::sample::select(vec![StepOrder::Before, StepOrder::After]),
+            pass_through in any::<bool>(),
+            policy in proptest::sample::select(
+                vec![StepPolicy::SkipNonLetters, StepPolicy::StepOnAll],
+            ),
+            message in "[A-Za-z ,.]{0,300}",
+        ) {
+            let mut machine = match model {
+                EnigmaModel::M3 => MachineConfig::seeded(seed),
+                model => MachineConfig::new(model),
+            }
+            .positions(&positions[..model.rotor_count()])
+            .build()
+            .unwrap();
+            machine.set_step_order(step_order);
+            machine.set_pass_through(pass_through);
+            let mut scalar = machine.clone();
+
+            let (encrypted, stats) = scalar.encrypt_message(&message, policy);
+            let mut fast_stats = EncryptStats::default();
+            let fast =
+                machine.encrypt_bytes_with_stats(message.as_bytes(), &mut fast_stats, policy);
+            prop_assert_eq!(fast, encrypted.into_bytes());
+            prop_assert_eq!(fast_stats, stats);
+            prop_assert_eq!(machine, scalar);
+        }
+
+        #[test]
+        fn prop_round_trip(
+            rotors in proptest::sample::subsequence(available_rotors(), 3).prop_shuffle(),
+            rings in (0..26usize, 0..26usize, 0..26usize),
+            positions in (0..26usize, 0..26usize, 0..26usize),
+            reflector in proptest::sample::select(available_reflectors()),
+            plugboard in plugboard(),
+            message in "[A-Z]{0,300}",
+        ) {
+            let rotors = (rotors[0], rotors[1], rotors[2]);
+            let mut machine = Machine::new(rotors, rings, positions, reflector, &plugboard);
+            let mut fresh = machine.clone();
+
+            let encrypted: String = message
+                .chars()
+                .map(|c| {
+                    let e = wire_to_char(machine.encipher(char_to_wire(c)));
+                    prop_assert_ne!(c, e);
+                    Ok(e)
+                })
+                .collect::<Result<_, TestCaseError>>()?;
+            prop_assert_eq!(fresh.encrypt(&encrypted), message);
+        }
+    }
 }
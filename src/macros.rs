@@ -0,0 +1,114 @@
+/// Creates one of the historical [`Rotor`](crate::Rotor)s from its name, checking the name at
+/// compile time. Takes the same names as [`Rotor::from_name`](crate::Rotor::from_name), optionally
+/// followed by the ring setting and position, which otherwise default to 0.
+///
+/// # Examples
+///
+/// ```
+/// use enigma::{rotor, Rotor};
+/// assert_eq!(rotor!("I"), Rotor::i(0, 0));
+/// assert_eq!(rotor!("NORWAY-II", 1, 2), Rotor::norway_ii(1, 2));
+/// ```
+///
+/// Unknown names don't compile:
+///
+/// ```compile_fail
+/// let rotor = enigma::rotor!("IXX");
+/// ```
+#[macro_export]
+macro_rules! rotor {
+    (@name "I") => { $crate::RotorName::I };
+    (@name "II") => { $crate::RotorName::II };
+    (@name "III") => { $crate::RotorName::III };
+    (@name "IV") => { $crate::RotorName::IV };
+    (@name "V") => { $crate::RotorName::V };
+    (@name "VI") => { $crate::RotorName::VI };
+    (@name "VII") => { $crate::RotorName::VII };
+    (@name "VIII") => { $crate::RotorName::VIII };
+    (@name "NORWAY-I") => { $crate::RotorName::NorwayI };
+    (@name "NORWAY-II") => { $crate::RotorName::NorwayII };
+    (@name "NORWAY-III") => { $crate::RotorName::NorwayIII };
+    (@name "NORWAY-IV") => { $crate::RotorName::NorwayIV };
+    (@name "NORWAY-V") => { $crate::RotorName::NorwayV };
+    (@name "G-I") => { $crate::RotorName::GI };
+    (@name "G-II") => { $crate::RotorName::GII };
+    (@name "G-III") => { $crate::RotorName::GIII };
+    (@name "RAILWAY-I") => { $crate::RotorName::RailwayI };
+    (@name "RAILWAY-II") => { $crate::RotorName::RailwayII };
+    (@name "RAILWAY-III") => { $crate::RotorName::RailwayIII };
+    (@name "BETA") => { $crate::RotorName::Beta };
+    (@name "GAMMA") => { $crate::RotorName::Gamma };
+    (@name $name:literal) => {
+        compile_error!(concat!("unknown rotor name ", stringify!($name)))
+    };
+    // Names are forwarded as token trees, which unlike `literal` fragments can still be matched
+    // against the literal names above.
+    ($name:tt) => {
+        $crate::rotor!($name, 0, 0)
+    };
+    ($name:tt, $ring_setting:expr, $position:expr) => {
+        $crate::Rotor::named($crate::rotor!(@name $name), $ring_setting, $position)
+    };
+}
+
+/// Creates one of the historical [`Reflector`](crate::Reflector)s from its name, checking the
+/// name at compile time. Takes the same names as
+/// [`Reflector::from_name`](crate::Reflector::from_name).
+///
+/// # Examples
+///
+/// ```
+/// use enigma::{reflector, Reflector};
+/// assert_eq!(reflector!("B"), Reflector::b());
+/// ```
+///
+/// Unknown names don't compile:
+///
+/// ```compile_fail
+/// let reflector = enigma::reflector!("E");
+/// ```
+#[macro_export]
+macro_rules! reflector {
+    ("A") => {
+        $crate::Reflector::a()
+    };
+    ("B") => {
+        $crate::Reflector::b()
+    };
+    ("C") => {
+        $crate::Reflector::c()
+    };
+    ("D") => {
+        $crate::Reflector::kd_default()
+    };
+    ("NORWAY") => {
+        $crate::Reflector::norway()
+    };
+    ("G") => {
+        $crate::Reflector::g()
+    };
+    ("RAILWAY") => {
+        $crate::Reflector::railway()
+    };
+    ("B-THIN") => {
+        $crate::Reflector::b_thin()
+    };
+    ("C-THIN") => {
+        $crate::Reflector::c_thin()
+    };
+    ($name:literal) => {
+        compile_error!(concat!("unknown reflector name ", stringify!($name)))
+    };
+}
+
+/// Emits a [`tracing`](https://docs.rs/tracing) event at trace level, taking the same arguments
+/// as `tracing::trace!`. Without the `tracing` feature this expands to nothing, so the arguments
+/// aren't even evaluated.
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::trace!($($arg)*);
+    };
+}
+
+pub(crate) use trace;
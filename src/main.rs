@@ -1,148 +1,499 @@
+use clap::builder::{PossibleValuesParser, TypedValueParser};
 use clap::Parser;
-use enigma::{Machine, ALPHABET_SIZE};
-use std::fmt::Display;
+use enigma::convert::try_char_to_wire;
+use enigma::{
+    analysis, available_reflectors, available_rotors, ClockInt, EncryptStats, Machine,
+    MachineConfig, Plugboard, RotorName,
+};
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
 
 /// Encrypt/decrypt a message using a simulation of the Enigma machine.
 #[derive(Parser, Debug)]
 struct Cli {
     /// Comma-seperated list of length 3 of the names of which 3 rotors to use.
-    #[arg(short, long)]
+    #[arg(
+        short,
+        long,
+        required_unless_present = "key",
+        value_parser = PossibleValuesParser::new(available_rotors())
+            .try_map(|name| RotorName::try_from(name.as_str())),
+    )]
     #[clap(use_value_delimiter = true)]
-    names: Vec<RotorNames>,
+    names: Vec<RotorName>,
 
-    /// Comma-seperated list of numbers of length 3 corresponding to the ring settings of the 3
-    /// rotors.
-    #[arg(short, long)]
+    /// Comma-seperated list of length 3 of the ring settings of the 3 rotors, either all as numbers
+    /// or all as letters, e.g. '1,1,1' or 'B,B,B'. Numbers count from 0, so '0' and 'A' are both
+    /// the ring setting listed as 01 on key sheets.
+    #[arg(short, long, required_unless_present = "key")]
     #[clap(use_value_delimiter = true)]
-    settings: Vec<usize>,
+    settings: Vec<String>,
 
     /// Reflector type.
-    #[arg(short, long)]
-    reflector: ReflectorNames,
+    #[arg(
+        short,
+        long,
+        required_unless_present = "key",
+        value_parser = PossibleValuesParser::new(available_reflectors()),
+    )]
+    reflector: Option<String>,
 
-    /// Plugboard connections as space-separated pairs of letters, e.g. 'AB CD' to swap the letters
-    /// A and B, and the letters C and D.
-    #[arg(short, long, default_value = "")]
+    /// Plugboard connections as pairs of letters, e.g. 'AB CD' or 'ABCD' to swap the letters A and
+    /// B, and the letters C and D.
+    #[arg(short, long, default_value = "", value_parser = parse_connections)]
     connections: String,
 
-    /// Comma-seperated list of numbers of length 3 corresponding to the initial rotor positions.
-    /// Each of these is taken modulo `crate::ALPHABET_SIZE`.
-    #[arg(short, long)]
+    /// Comma-seperated list of length 3 of the initial rotor positions, either all as numbers or
+    /// all as letters, e.g. '0,4,25' or 'A,E,Z'. Numbers count from 0 and are taken modulo 26.
+    #[arg(short, long, required_unless_present = "key")]
     #[clap(use_value_delimiter = true)]
-    positions: Vec<usize>,
+    positions: Vec<String>,
+
+    /// Full key as segments of rotor names, ring settings, rotor positions, reflector and optional
+    /// plugboard connections, separated by '/' or ':', e.g. 'I,II,III:1,1,1:A,A,A:B:AB CD'. Ring
+    /// settings and positions may be given as numbers or letters. Four rotors select the M4.
+    /// Replaces the other key arguments.
+    #[arg(
+        short,
+        long,
+        value_parser = MachineConfig::from_str,
+        conflicts_with_all = ["names", "settings", "reflector", "connections", "positions"],
+    )]
+    key: Option<MachineConfig>,
+
+    /// Comma-separated list of length 3 overriding the notches of the 3 rotors, as the letters
+    /// showing when each turns over the next, e.g. 'Q,E,V'. Separate several notches on one rotor
+    /// with '+', e.g. 'Q,E,M+Z'.
+    #[arg(long, value_parser = parse_notches)]
+    notches: Option<Notches>,
+
+    /// Reject ring settings and positions outside 0-25 instead of wrapping them around.
+    #[arg(long)]
+    strict: bool,
+
+    /// Take ring settings and positions only as letters, with 'A' for the ring setting listed as
+    /// 01 on key sheets, and show them as letters in the key printed with --verbose.
+    #[arg(long)]
+    letters: bool,
+
+    /// Only check that the key is valid, printing 'OK' or the error, without reading a message.
+    /// Exits with a non-zero status if it isn't.
+    #[arg(long, conflicts_with_all = ["message", "input", "output"])]
+    validate: bool,
+
+    /// Check the output against an expected result, e.g. from a known-answer test, printing 'OK'
+    /// or where they differ instead of the output. Exits with a non-zero status if they differ.
+    #[arg(long, conflicts_with = "output")]
+    verify: Option<String>,
+
+    /// Encrypt each line of the message separately, starting each from the given rotor positions,
+    /// rather than as one continuous message.
+    #[arg(long)]
+    reset_per_line: bool,
+
+    /// Keep the line breaks of the message in the output, rather than leaving them out. The rotors
+    /// carry on from one line to the next, unlike with --reset-per-line.
+    #[arg(long, conflicts_with = "reset_per_line")]
+    keep_lines: bool,
+
+    /// Treat the input as several messages separated by DELIMITER on a line of its own, or by
+    /// blank lines if no delimiter is given. Each message starts from the given rotor positions
+    /// and its output is written on its own line.
+    #[arg(
+        long,
+        value_name = "DELIMITER",
+        num_args = 0..=1,
+        default_missing_value = "",
+        conflicts_with_all = ["reset_per_line", "keep_lines"],
+    )]
+    split: Option<String>,
+
+    /// Print the key in use, and report each skipped non-letter character and how many were
+    /// skipped.
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// File to read the message from instead of stdin. The file is streamed rather than read
+    /// into memory.
+    #[arg(short, long, conflicts_with = "message")]
+    input: Option<PathBuf>,
+
+    /// File to write the output to instead of stdout.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
 
-    /// Message to encrypt/decrypt. If not given reads from stdin.
+    /// Message to encrypt/decrypt. If not given reads from the input file or stdin.
     message: Option<String>,
 }
 
-#[derive(clap::ValueEnum, Clone, Debug)]
-#[clap(rename_all = "UPPER")]
-#[allow(clippy::upper_case_acronyms)]
-enum RotorNames {
-    I,
-    II,
-    III,
-    IV,
-    V,
-    VI,
-    VII,
-    VIII,
-}
-
-#[derive(clap::ValueEnum, Clone, Debug)]
-#[clap(rename_all = "UPPER")]
-#[allow(clippy::upper_case_acronyms)]
-enum ReflectorNames {
-    A,
-    B,
-    C,
-}
-
-macro_rules! display_enums {
-    ($t:ty) => {
-        impl Display for $t {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                write!(f, "{:?}", self)
-            }
+/// Reader that reports each skipped non-letter byte as it's read, for `--verbose`.
+struct VerboseReader<R> {
+    inner: R,
+}
+
+impl<R: Read> Read for VerboseReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        for b in buf[..n].iter().filter(|b| !b.is_ascii_alphabetic()) {
+            eprintln!("Skipping char: {}", b.escape_ascii());
+        }
+        Ok(n)
+    }
+}
+
+/// Where the message to encrypt comes from.
+enum Input<'a> {
+    Message(&'a str),
+    Reader(Box<dyn Read>),
+}
+
+fn encrypt_message(
+    machine: &mut Machine,
+    message: &str,
+    output: &mut impl Write,
+    verbose: bool,
+) -> io::Result<EncryptStats> {
+    if verbose {
+        for c in message.chars().filter(|c| !c.is_ascii_alphabetic()) {
+            eprintln!("Skipping char: {}", c);
+        }
+    }
+    let (encrypted, stats) = machine.encrypt_with_stats(message);
+    output.write_all(encrypted.as_bytes())?;
+    Ok(stats)
+}
+
+/// Encrypts each line with [`Machine::encrypt_lines`], keeping the line breaks, for
+/// `--keep-lines` and `--reset-per-line`. Each line starts from the start position of the machine
+/// if `reset` is set.
+fn encrypt_lines(
+    machine: &mut Machine,
+    input: &str,
+    output: &mut impl Write,
+    verbose: bool,
+    reset: bool,
+) -> io::Result<EncryptStats> {
+    if verbose {
+        let skipped = input
+            .chars()
+            .filter(|&c| !c.is_ascii_alphabetic() && c != '\r' && c != '\n');
+        for c in skipped {
+            eprintln!("Skipping char: {}", c);
+        }
+    }
+    if !reset {
+        let (encrypted, stats) = machine.encrypt_lines_with_stats(input);
+        output.write_all(encrypted.as_bytes())?;
+        return Ok(stats);
+    }
+
+    let mut total = EncryptStats::default();
+    for line in input.split_inclusive('\n') {
+        machine.reset();
+        let (encrypted, stats) = machine.encrypt_lines_with_stats(line);
+        output.write_all(encrypted.as_bytes())?;
+
+        total.letters += stats.letters;
+        total.skipped += stats.skipped;
+        total.total_steps += stats.total_steps;
+    }
+    Ok(total)
+}
+
+/// Splits `input` into messages at lines that are just `delimiter`, ignoring surrounding
+/// whitespace, or at blank lines if `delimiter` is empty. The lines of each message are joined
+/// without their line breaks, and empty messages are left out.
+fn split_messages(input: &str, delimiter: &str) -> Vec<String> {
+    let mut messages = vec![String::new()];
+    for line in input.lines() {
+        if line.trim() == delimiter.trim() {
+            messages.push(String::new());
+        } else {
+            let message = messages.last_mut().expect("there is always a message");
+            message.push_str(line);
+        }
+    }
+    messages.retain(|m| !m.trim().is_empty());
+    messages
+}
+
+/// Encrypts each message of `input` separated by `delimiter` from the start position of the
+/// machine, for `--split`, writing each on its own line.
+fn encrypt_split(
+    machine: &mut Machine,
+    input: &str,
+    delimiter: &str,
+    output: &mut impl Write,
+    verbose: bool,
+) -> io::Result<EncryptStats> {
+    let mut total = EncryptStats::default();
+    for message in split_messages(input, delimiter) {
+        machine.reset();
+        let stats = encrypt_message(machine, &message, output, verbose)?;
+        writeln!(output)?;
+
+        total.letters += stats.letters;
+        total.skipped += stats.skipped;
+        total.total_steps += stats.total_steps;
+    }
+    Ok(total)
+}
+
+/// How the lines of the message are treated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Lines<'a> {
+    /// Line breaks are skipped like any other non-letter.
+    Join,
+    /// Line breaks are kept, and the rotors carry on from one line to the next.
+    Keep,
+    /// Line breaks are kept, and each line starts from the given rotor positions.
+    Reset,
+    /// The message is split into several at the delimiter, see [`split_messages`], and each
+    /// starts from the given rotor positions.
+    Split(&'a str),
+}
+
+fn encrypt_to(
+    machine: &mut Machine,
+    input: Input,
+    mut output: impl Write,
+    verbose: bool,
+    lines: Lines,
+) -> io::Result<EncryptStats> {
+    let reset = lines == Lines::Reset;
+    let stats = match (input, lines) {
+        (Input::Message(message), Lines::Split(delimiter)) => {
+            encrypt_split(machine, message, delimiter, &mut output, verbose)?
+        }
+        (Input::Reader(mut reader), Lines::Split(delimiter)) => {
+            let mut input = String::new();
+            reader.read_to_string(&mut input)?;
+            encrypt_split(machine, &input, delimiter, &mut output, verbose)?
+        }
+        (Input::Message(message), Lines::Join) => {
+            encrypt_message(machine, message, &mut output, verbose)?
+        }
+        (Input::Message(message), _) => {
+            encrypt_lines(machine, message, &mut output, verbose, reset)?
+        }
+        (Input::Reader(reader), Lines::Join) if verbose => {
+            machine.process_reader_with_stats(VerboseReader { inner: reader }, &mut output)?
+        }
+        (Input::Reader(reader), Lines::Join) => {
+            machine.process_reader_with_stats(reader, &mut output)?
+        }
+        (Input::Reader(mut reader), _) => {
+            let mut input = String::new();
+            reader.read_to_string(&mut input)?;
+            encrypt_lines(machine, &input, &mut output, verbose, reset)?
         }
     };
+    output.flush()?;
+    Ok(stats)
 }
 
-display_enums!(RotorNames);
-display_enums!(ReflectorNames);
+/// Notch positions of each rotor, from `--notches`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Notches(Vec<Vec<usize>>);
+
+fn parse_notches(notches: &str) -> Result<Notches, String> {
+    notches
+        .split(',')
+        .map(|rotor| {
+            rotor
+                .split('+')
+                .map(|notch| {
+                    let notch = notch.trim();
+                    let mut chars = notch.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) if c.is_ascii_alphabetic() => ClockInt::try_from(c)
+                            .map(ClockInt::value)
+                            .map_err(|e| e.to_string()),
+                        _ => Err(format!(
+                            "invalid notch '{}': must be a single letter",
+                            notch
+                        )),
+                    }
+                })
+                .collect()
+        })
+        .collect::<Result<_, _>>()
+        .map(Notches)
+}
+
+/// Checks plugboard connections, keeping them as given for [`MachineConfig::plugboard`].
+fn parse_connections(connections: &str) -> Result<String, String> {
+    Plugboard::new(connections)
+        .map(|_| connections.to_string())
+        .map_err(|e| e.to_string())
+}
+
+fn parse_key_number(s: &str) -> Result<usize, String> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_alphabetic() => ClockInt::try_from(c)
+            .map(ClockInt::value)
+            .map_err(|e| e.to_string()),
+        _ => s
+            .parse()
+            .map_err(|_| format!("'{}' is neither a number nor a letter", s)),
+    }
+}
+
+/// Parses ring settings or positions, `what` naming them in errors. They must be all letters or
+/// all numbers, or only letters if `letters` is set.
+fn parse_settings(values: &[String], what: &str, letters: bool) -> Result<Vec<usize>, String> {
+    let is_letter = |s: &String| s.len() == 1 && s.as_bytes()[0].is_ascii_alphabetic();
+    if letters {
+        return values
+            .iter()
+            .map(|s| {
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => try_char_to_wire(c).map(usize::from).ok(),
+                    _ => None,
+                }
+                .ok_or_else(|| format!("invalid {}: '{}' is not a letter", what, s))
+            })
+            .collect();
+    }
+    if values.iter().any(is_letter) && !values.iter().all(is_letter) {
+        return Err(format!(
+            "{}s must be all letters or all numbers, found '{}'",
+            what,
+            values.join(",")
+        ));
+    }
+
+    values
+        .iter()
+        .map(|s| parse_key_number(s).map_err(|e| format!("invalid {}: {}", what, e)))
+        .collect()
+}
 
 fn main() {
     let cli = Cli::parse();
 
-    if cli.names.len() != 3 {
-        eprintln!(
-            "Error: 3 rotor names should be given, {} received",
-            cli.names.len()
-        );
-        std::process::exit(1);
-    }
-    let rotor_names = (
-        cli.names[0].to_string(),
-        cli.names[1].to_string(),
-        cli.names[2].to_string(),
-    );
-
-    if cli.settings.len() != 3 {
-        eprintln!(
-            "Error: 3 rotor settings should be given, {} received",
-            cli.settings.len()
-        );
-        std::process::exit(1);
+    let config = match cli.key {
+        Some(config) => config,
+        None => {
+            let settings = parse_settings(&cli.settings, "ring setting", cli.letters);
+            let positions = parse_settings(&cli.positions, "rotor position", cli.letters);
+            match settings.and_then(|settings| positions.map(|positions| (settings, positions))) {
+                Ok((settings, positions)) => MachineConfig::default()
+                    .rotor_names(&cli.names)
+                    .ring_settings(&settings)
+                    .positions(&positions)
+                    .reflector(&cli.reflector.expect("reflector is required without a key"))
+                    .plugboard(&cli.connections),
+                Err(err) => {
+                    eprintln!("Error: {}", err);
+                    std::process::exit(1);
+                }
+            }
+        }
+    };
+
+    if cli.strict {
+        if let Err(err) = config.check_ranges() {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        }
     }
-    let settings = (cli.settings[0], cli.settings[1], cli.settings[2]);
 
-    if cli.positions.len() != 3 {
-        eprintln!(
-            "Error: 3 rotor positions should be given, {} received",
-            cli.positions.len()
-        );
-        std::process::exit(1);
-    }
-    let positions = (
-        cli.positions[0] % ALPHABET_SIZE,
-        cli.positions[1] % ALPHABET_SIZE,
-        cli.positions[2] % ALPHABET_SIZE,
-    );
-
-    let mut machine = Machine::new(
-        (
-            rotor_names.0.as_str(),
-            rotor_names.1.as_str(),
-            rotor_names.2.as_str(),
-        ),
-        settings,
-        positions,
-        cli.reflector.to_string().as_str(),
-        &cli.connections,
-    );
-
-    let mut buffer = String::new();
-    let message = match &cli.message {
-        Some(m) => m,
-        None => {
-            let stdin = std::io::stdin();
+    let config = match &cli.notches {
+        Some(Notches(notches)) => config.notches(notches),
+        None => config,
+    };
+    let mut machine = match config.build() {
+        Ok(machine) => machine,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        }
+    };
 
-            for line in stdin.lines() {
-                match line {
-                    Ok(ref line) => {
-                        buffer.push_str(line);
-                    }
-                    Err(err) => {
-                        eprintln!("{}", err);
-                        std::process::exit(1);
-                    }
-                }
+    if cli.verbose {
+        eprintln!("Key: {}", config.key_string(cli.letters));
+    }
+
+    if cli.validate {
+        println!("OK");
+        return;
+    }
+
+    let lines = if let Some(delimiter) = &cli.split {
+        Lines::Split(delimiter)
+    } else if cli.reset_per_line {
+        Lines::Reset
+    } else if cli.keep_lines {
+        Lines::Keep
+    } else {
+        Lines::Join
+    };
+
+    let input = match (&cli.message, &cli.input) {
+        (Some(message), _) => Input::Message(message),
+        (None, Some(path)) => match File::open(path) {
+            Ok(file) => Input::Reader(Box::new(file)),
+            Err(err) => {
+                eprintln!("Error: could not open {}: {}", path.display(), err);
+                std::process::exit(1);
+            }
+        },
+        (None, None) => Input::Reader(Box::new(io::stdin().lock())),
+    };
+
+    if let Some(expected) = &cli.verify {
+        let mut output = Vec::new();
+        if let Err(err) = encrypt_to(&mut machine, input, &mut output, cli.verbose, lines) {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        }
+
+        let actual = String::from_utf8_lossy(&output);
+        if !analysis::diff(expected, &actual).is_empty() {
+            eprint!("{}", analysis::format_diff(expected, &actual));
+            std::process::exit(1);
+        }
+        println!("OK");
+        return;
+    }
+
+    let result = match &cli.output {
+        Some(path) => File::create(path).and_then(|file| {
+            encrypt_to(
+                &mut machine,
+                input,
+                BufWriter::new(file),
+                cli.verbose,
+                lines,
+            )
+        }),
+        None => {
+            let mut stdout = BufWriter::new(io::stdout().lock());
+            let stats = encrypt_to(&mut machine, input, &mut stdout, cli.verbose, lines);
+            if lines != Lines::Join {
+                // Each line already ends with a newline.
+                stats
+            } else {
+                stats.and_then(|stats| writeln!(stdout).and(stdout.flush()).map(|_| stats))
             }
-            &buffer
         }
     };
 
-    println!("{}", machine.encrypt(message));
+    match result {
+        Ok(stats) if cli.verbose && stats.skipped > 0 => {
+            eprintln!("Skipped {} non-letter characters", stats.skipped);
+        }
+        Ok(_) => {}
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -154,4 +505,113 @@ mod tests {
         use clap::CommandFactory;
         Cli::command().debug_assert()
     }
+
+    #[test]
+    fn test_key_arg() {
+        let parse =
+            |key| Cli::try_parse_from(["enigma", "--key", key, "MESSAGE"]).map(|cli| cli.key);
+        assert_eq!(
+            parse("I,II,III:1,1,1:A,B,C:B:AB CD").unwrap(),
+            Some(
+                MachineConfig::default()
+                    .ring_settings(&[1, 1, 1])
+                    .positions(&[0, 1, 2])
+                    .plugboard("AB CD")
+            )
+        );
+        assert_eq!(
+            parse("BETA,II,IV,I/A,A,A,V/V,J,N,A/B-THIN").unwrap(),
+            Some("BETA,II,IV,I:0,0,0,21:21,9,13,0:B-THIN".parse().unwrap())
+        );
+
+        assert!(parse("I,II,III:1,1,1:A,A,A").is_err());
+        assert!(parse("I,II,III:1,1,1:A,A,A:B:AB:CD").is_err());
+        assert!(parse("I,II,III:1,AB,1:A,A,A:B").is_err());
+        assert!(parse("I,II,III:1,B,3:A,A,A:B").is_err());
+    }
+
+    #[test]
+    fn test_parse_settings() {
+        let values = |s: &str| s.split(',').map(String::from).collect::<Vec<_>>();
+        let parse = |s, letters| parse_settings(&values(s), "ring setting", letters);
+        assert_eq!(parse("1,2,25", false), Ok(vec![1, 2, 25]));
+        assert_eq!(parse("B,c,Z", false), Ok(vec![1, 2, 25]));
+        assert_eq!(
+            parse("A,1,B", false),
+            Err("ring settings must be all letters or all numbers, found 'A,1,B'".to_string())
+        );
+        assert!(parse("1,AB,1", false).is_err());
+
+        // With --letters, 'A' is still 0, and numbers are rejected.
+        assert_eq!(parse("A,B,Z", true), Ok(vec![0, 1, 25]));
+        assert_eq!(
+            parse("A,1,B", true),
+            Err("invalid ring setting: '1' is not a letter".to_string())
+        );
+        assert!(parse("A,BC,D", true).is_err());
+    }
+
+    #[test]
+    fn test_encrypt_lines() {
+        let mut machine = MachineConfig::default().build().unwrap();
+        let mut output = Vec::new();
+        let input = "AAA\r\nAAA, B\r\n";
+        let stats = encrypt_lines(&mut machine, input, &mut output, false, true).unwrap();
+        assert_eq!(output, b"BDZ\r\nBDZC\r\n");
+        assert_eq!(stats.letters, 7);
+        assert_eq!(stats.skipped, 2);
+
+        let mut machine = MachineConfig::default().build().unwrap();
+        let mut output = Vec::new();
+        encrypt_lines(&mut machine, input, &mut output, false, false).unwrap();
+        assert_eq!(output, b"BDZ\r\nGOWJ\r\n");
+    }
+
+    #[test]
+    fn test_split_messages() {
+        assert_eq!(
+            split_messages("AAA\nBB\n\n  \nCCC\n\n", ""),
+            vec!["AAABB", "CCC"]
+        );
+        assert_eq!(
+            split_messages("AAA\n---\nBBB\n\nCCC\n --- \n---\n", "---"),
+            vec!["AAA", "BBBCCC"]
+        );
+        assert!(split_messages("", "").is_empty());
+
+        let mut machine = MachineConfig::default().build().unwrap();
+        let mut output = Vec::new();
+        let input = "AAA\n\nAAA\nA\n";
+        let stats = encrypt_split(&mut machine, input, "", &mut output, false).unwrap();
+        assert_eq!(output, b"BDZ\nBDZG\n");
+        assert_eq!(stats.letters, 7);
+    }
+
+    #[test]
+    fn test_parse_notches() {
+        assert_eq!(
+            parse_notches("Q,e,M+Z"),
+            Ok(Notches(vec![vec![16], vec![4], vec![12, 25]]))
+        );
+        assert_eq!(
+            parse_notches("Q,EE,V"),
+            Err("invalid notch 'EE': must be a single letter".to_string())
+        );
+        assert!(parse_notches("Q,,V").is_err());
+        assert!(parse_notches("Q,1,V").is_err());
+    }
+
+    #[test]
+    fn test_parse_connections() {
+        assert_eq!(parse_connections(""), Ok(String::new()));
+        assert_eq!(parse_connections("AB cd"), Ok("AB cd".to_string()));
+        assert_eq!(
+            parse_connections("AB AC"),
+            Err("letter 'A' is connected more than once".to_string())
+        );
+        assert_eq!(
+            parse_connections("ABC"),
+            Err("invalid plugboard connections 'ABC': must be pairs of letters".to_string())
+        );
+    }
 }
@@ -0,0 +1,180 @@
+use crate::machine::parse_positions;
+use crate::{ClockInt, InvalidArgsError, Machine};
+
+/// An operator following the German indicator procedure used until 1940, to send and receive
+/// whole messages with a [`Machine`].
+///
+/// Every message is sent with a new message key chosen by the operator. The rotors are set to the
+/// day's basic position, the Grundstellung, and the message key is enciphered twice to form a
+/// six-letter indicator. The rotors are then set to the message key to encipher the body. The
+/// receiving operator deciphers the indicator at the same Grundstellung to recover the message key.
+///
+/// # Examples
+///
+/// ```
+/// use enigma::{Machine, Operator};
+/// // Both operators set up their machines from the same key sheet.
+/// let key = "I,II,III/A,A,A/A,A,A/B/AB CD";
+/// let mut sender = Operator::new(key.parse().unwrap(), "QWE").unwrap();
+/// let mut receiver = Operator::new(key.parse().unwrap(), "QWE").unwrap();
+///
+/// // The sender picks the message key RTZ.
+/// let transmission = sender.transmit("RTZ", "ANGRIFFXUMXVIER").unwrap();
+/// assert_eq!(transmission.len(), 6 + 1 + 15);
+///
+/// assert_eq!(receiver.receive(&transmission).unwrap(), "ANGRIFFXUMXVIER");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Operator {
+    machine: Machine,
+    grund: (ClockInt, ClockInt, ClockInt),
+}
+
+impl Operator {
+    /// Creates a new [`Operator`] using `machine`, set up with the day's rotors, ring settings,
+    /// reflector and plugboard, and the day's Grundstellung `grund`, e.g. `"QWE"`.
+    ///
+    /// Returns an error if `grund` isn't three ASCII letters.
+    pub fn new(machine: Machine, grund: &str) -> Result<Self, InvalidArgsError> {
+        Ok(Self {
+            machine,
+            grund: parse_three_letters(grund, "Grundstellung")?,
+        })
+    }
+
+    /// The machine being operated.
+    pub fn machine(&self) -> &Machine {
+        &self.machine
+    }
+
+    /// Enciphers `body` with the message key `msg_key`, returning the transmission: the
+    /// six-letter indicator, a space and the enciphered body.
+    ///
+    /// Returns an error if `msg_key` isn't three ASCII letters.
+    pub fn transmit(&mut self, msg_key: &str, body: &str) -> Result<String, InvalidArgsError> {
+        let (indicator, body) =
+            encipher_with_indicator(&mut self.machine, self.grund, msg_key, body)?;
+        Ok(format!("{} {}", indicator, body))
+    }
+
+    /// Deciphers a `transmission` from [`Operator::transmit`], returning the body. The indicator
+    /// is the first six letters, so the spacing doesn't matter.
+    ///
+    /// Returns an error if the transmission is too short to hold an indicator, or if the
+    /// indicator doesn't decipher to a repeated message key, e.g. because it was garbled or the
+    /// machines are set up differently.
+    pub fn receive(&mut self, transmission: &str) -> Result<String, InvalidArgsError> {
+        let letters: String = transmission
+            .chars()
+            .filter(|c| c.is_ascii_alphabetic())
+            .collect();
+        if letters.len() < 6 {
+            return Err(InvalidArgsError::InvalidKey(format!(
+                "transmission '{}' is too short to hold an indicator",
+                transmission
+            )));
+        }
+        let (indicator, body) = letters.split_at(6);
+
+        self.machine.set_positions(self.grund);
+        let doubled_key = self.machine.encrypt(indicator);
+        let (key, repeat) = doubled_key.split_at(3);
+        if key != repeat {
+            return Err(InvalidArgsError::InvalidKey(format!(
+                "indicator '{}' deciphers to '{}', which doesn't repeat a message key",
+                indicator, doubled_key
+            )));
+        }
+
+        self.machine
+            .set_positions(parse_three_letters(key, "message key")?);
+        Ok(self.machine.encrypt(body))
+    }
+}
+
+/// Enciphers the doubled message key `msg_key` at the Grundstellung `grund` to form the
+/// indicator, then `body` at the message key, returning the indicator and the enciphered body.
+/// Shared by [`Operator::transmit`] and [`Machine::encrypt_with_indicator_procedure`].
+pub(crate) fn encipher_with_indicator(
+    machine: &mut Machine,
+    grund: (ClockInt, ClockInt, ClockInt),
+    msg_key: &str,
+    body: &str,
+) -> Result<(String, String), InvalidArgsError> {
+    let key = parse_three_letters(msg_key, "message key")?;
+
+    machine.set_positions(grund);
+    let indicator = machine.encrypt(&msg_key.repeat(2));
+    machine.set_positions(key);
+    Ok((indicator, machine.encrypt(body)))
+}
+
+pub(crate) fn parse_three_letters(
+    positions: &str,
+    what: &str,
+) -> Result<(ClockInt, ClockInt, ClockInt), InvalidArgsError> {
+    parse_positions(positions).ok_or_else(|| {
+        InvalidArgsError::InvalidKey(format!(
+            "{} must be three letters, found '{}'",
+            what, positions
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MachineConfig;
+
+    fn operator() -> Operator {
+        let machine = Machine::new(("II", "IV", "V"), (1, 20, 11), (0, 0, 0), "B", "AV BS CG");
+        Operator::new(machine, "WXC").unwrap()
+    }
+
+    #[test]
+    fn test_1930_manual_example() {
+        // The example message of the 1930 instruction manual: rotors II I III, ring settings
+        // 24 13 22, reflector A, Grundstellung FOL and message key ABL.
+        let machine = MachineConfig::default()
+            .rotors(&["II", "I", "III"])
+            .ring_settings(&[23, 12, 21])
+            .reflector("A")
+            .plugboard("AM FI NV PS TU WZ")
+            .build()
+            .unwrap();
+        let body = "FEINDLIQEINFANTERIEKOLONNEBEOBAQTETXANFANGSUEDAUSGANGBAERWALDEX\
+                    ENDEDREIKMOSTWAERTSNEUSTADT";
+        let transmission = "PKPJXI GCDSEAHUGWTQGRKVLFGXUCALXVYMIGMMNMFDXTGNVHVRMMEVOUYFZSLRH\
+                            DRRXFJWCFHUHMUNZEFRDISIKBGPMYVXUZ";
+
+        let mut sender = Operator::new(machine.clone(), "FOL").unwrap();
+        assert_eq!(sender.transmit("ABL", body).unwrap(), transmission);
+        let mut receiver = Operator::new(machine, "FOL").unwrap();
+        assert_eq!(receiver.receive(transmission), Ok(body.to_string()));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut sender = operator();
+        let mut receiver = operator();
+        for (key, body) in [
+            ("BLA", "HELLOXWORLD"),
+            ("ZZZ", "ANOTHERXMESSAGE"),
+            ("ABC", ""),
+        ] {
+            let transmission = sender.transmit(key, body).unwrap();
+            assert_eq!(receiver.receive(&transmission), Ok(body.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(Operator::new(Machine::default_m3(), "AB").is_err());
+        assert!(operator().transmit("AB1", "HELLO").is_err());
+        assert!(operator().receive("ABCDE").is_err());
+
+        let transmission = operator().transmit("BLA", "HELLOXWORLD").unwrap();
+        let garbled = format!("A{}", &transmission[1..]);
+        assert!(operator().receive(&garbled).is_err());
+    }
+}
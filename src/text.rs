@@ -0,0 +1,309 @@
+//! Preparing natural text for the machine, which can only encipher the letters A-Z.
+
+/// Default table used by [`normalize`], following the German operators' conventions.
+///
+/// | Character | Replacement |
+/// |-----------|-------------|
+/// | `.`       | `X`         |
+/// | `,`       | `Y`         |
+/// | `?`       | `UD`        |
+/// | `:`       | `XX`        |
+/// | `-`       | `YY`        |
+/// | `0`-`9`   | The words in [`GERMAN_DIGITS`], `NULL` to `NEUN` |
+///
+/// Spaces and anything else not in the table are left alone, and so are skipped when enciphering.
+pub const DEFAULT_NORMALIZATION: &[(char, &str)] = &[
+    ('.', "X"),
+    (',', "Y"),
+    ('?', "UD"),
+    (':', "XX"),
+    ('-', "YY"),
+    ('0', "NULL"),
+    ('1', "EINS"),
+    ('2', "ZWO"),
+    ('3', "DREI"),
+    ('4', "VIER"),
+    ('5', "FUENF"),
+    ('6', "SECHS"),
+    ('7', "SIEBEN"),
+    ('8', "ACHT"),
+    ('9', "NEUN"),
+];
+
+/// Replaces each character of `text` that appears in `table` with its replacement, leaving all
+/// other characters unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use enigma::text::{normalize, DEFAULT_NORMALIZATION};
+/// assert_eq!(normalize("Gruppe 3, Ost.", DEFAULT_NORMALIZATION), "Gruppe DREIY OstX");
+/// ```
+pub fn normalize(text: &str, table: &[(char, &str)]) -> String {
+    let mut normalized = String::with_capacity(text.len());
+    for c in text.chars() {
+        match table.iter().find(|(from, _)| *from == c) {
+            Some((_, to)) => normalized.push_str(to),
+            None => normalized.push(c),
+        }
+    }
+
+    normalized
+}
+
+/// German words for the digits `0`-`9`, as in [`DEFAULT_NORMALIZATION`], with `ZWO` for two so
+/// it can't be misheard as `DREI`.
+pub const GERMAN_DIGITS: [&str; 10] = [
+    "NULL", "EINS", "ZWO", "DREI", "VIER", "FUENF", "SECHS", "SIEBEN", "ACHT", "NEUN",
+];
+
+/// English words for the digits `0`-`9`.
+pub const ENGLISH_DIGITS: [&str; 10] = [
+    "ZERO", "ONE", "TWO", "THREE", "FOUR", "FIVE", "SIX", "SEVEN", "EIGHT", "NINE",
+];
+
+/// The letters on the top row of the German keyboard in place of the digits `0`-`9`, `Q` for 1
+/// through `O` for 9 and `P` for 0, like the figure shift of a teleprinter.
+pub const TOP_ROW_DIGITS: [&str; 10] = ["P", "Q", "W", "E", "R", "T", "Z", "U", "I", "O"];
+
+/// How the digits `0`-`9` are treated before enciphering, since the machine has no keys for them.
+/// See [`Machine::encrypt_with_digit_policy`](crate::Machine::encrypt_with_digit_policy).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DigitPolicy<'a> {
+    /// Digits are skipped like any other character that isn't a letter.
+    #[default]
+    Skip,
+    /// Each digit is replaced by the code at its index, e.g. [`GERMAN_DIGITS`],
+    /// [`ENGLISH_DIGITS`] or [`TOP_ROW_DIGITS`], or a table built at runtime.
+    Replace(&'a [&'a str; 10]),
+}
+
+/// Spells out each digit of `text` with the word at its index in `table`, leaving everything else
+/// unchanged. [`GERMAN_DIGITS`] follows the same conventions as [`DEFAULT_NORMALIZATION`]; other
+/// tables can be used for other languages or armies' conventions.
+///
+/// # Examples
+///
+/// ```
+/// use enigma::text::{spell_digits, GERMAN_DIGITS};
+/// assert_eq!(spell_digits("KM 12", &GERMAN_DIGITS), "KM EINSZWO");
+///
+/// let italian = [
+///     "ZERO", "UNO", "DUE", "TRE", "QUATTRO", "CINQUE", "SEI", "SETTE", "OTTO", "NOVE",
+/// ];
+/// assert_eq!(spell_digits("KM 12", &italian), "KM UNODUE");
+/// ```
+pub fn spell_digits(text: &str, table: &[&str; 10]) -> String {
+    let mut spelled = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c.to_digit(10) {
+            Some(d) => spelled.push_str(table[d as usize]),
+            None => spelled.push(c),
+        }
+    }
+
+    spelled
+}
+
+/// Replaces each digit of `text` according to `policy`, leaving everything else unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use enigma::text::{replace_digits, DigitPolicy, ENGLISH_DIGITS, TOP_ROW_DIGITS};
+/// assert_eq!(replace_digits("U 534", DigitPolicy::Replace(&ENGLISH_DIGITS)), "U FIVETHREEFOUR");
+/// assert_eq!(replace_digits("U 534", DigitPolicy::Replace(&TOP_ROW_DIGITS)), "U TER");
+/// assert_eq!(replace_digits("U 534", DigitPolicy::Skip), "U 534");
+/// ```
+pub fn replace_digits(text: &str, policy: DigitPolicy<'_>) -> String {
+    match policy {
+        DigitPolicy::Skip => text.to_string(),
+        DigitPolicy::Replace(codes) => spell_digits(text, codes),
+    }
+}
+
+/// The inverse of [`replace_digits`], replacing each code in `text` with its digit. Earlier and
+/// then longer codes are replaced first, and the match is case-insensitive.
+///
+/// Every occurrence of a code is replaced, including inside words, so this only gives back the
+/// original text when the codes don't otherwise appear in it. That's rarely true of
+/// [`TOP_ROW_DIGITS`], whose codes are single letters.
+///
+/// # Examples
+///
+/// ```
+/// use enigma::text::{restore_digits, DigitPolicy, GERMAN_DIGITS};
+/// assert_eq!(restore_digits("UXFUENFDREIVIER", DigitPolicy::Replace(&GERMAN_DIGITS)), "UX534");
+/// ```
+pub fn restore_digits(text: &str, policy: DigitPolicy<'_>) -> String {
+    let codes = match policy {
+        DigitPolicy::Skip => return text.to_string(),
+        DigitPolicy::Replace(codes) => codes,
+    };
+
+    let mut restored = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let code = codes
+            .iter()
+            .enumerate()
+            .filter(|(_, code)| {
+                !code.is_empty()
+                    && rest
+                        .get(..code.len())
+                        .is_some_and(|start| start.eq_ignore_ascii_case(code))
+            })
+            .max_by_key(|(_, code)| code.len());
+        match code {
+            Some((digit, code)) => {
+                restored.push(char::from(b'0' + digit as u8));
+                rest = &rest[code.len()..];
+            }
+            None => {
+                restored.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    restored
+}
+
+/// International Morse code for the letters `A`-`Z` and then the digits `0`-`9`.
+const MORSE: [&str; 36] = [
+    ".-", "-...", "-.-.", "-..", ".", "..-.", "--.", "....", "..", ".---", "-.-", ".-..", "--",
+    "-.", "---", ".--.", "--.-", ".-.", "...", "-", "..-", "...-", ".--", "-..-", "-.--", "--..",
+    "-----", ".----", "..---", "...--", "....-", ".....", "-....", "--...", "---..", "----.",
+];
+
+/// Converts `text`, e.g. ciphertext in five-letter groups, to International Morse code. Letters
+/// are separated by a space and the groups, or any other words, by ` / `. Letters are converted
+/// regardless of case, and anything that isn't a letter, digit or whitespace is left out.
+///
+/// # Examples
+///
+/// ```
+/// use enigma::text::to_morse;
+/// assert_eq!(to_morse("SOS ENI"), "... --- ... / . -. ..");
+/// ```
+pub fn to_morse(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter_map(|c| match c.to_ascii_uppercase() {
+                    c @ 'A'..='Z' => Some(MORSE[c as usize - 'A' as usize]),
+                    c @ '0'..='9' => Some(MORSE[26 + c as usize - '0' as usize]),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+/// The inverse of [`to_morse`], reading letters separated by whitespace and words separated by
+/// `/`. Gives [`None`] if there's a code that isn't a letter or digit.
+///
+/// # Examples
+///
+/// ```
+/// use enigma::text::from_morse;
+/// assert_eq!(from_morse("... --- ... / . -. .."), Some("SOS ENI".to_string()));
+/// assert_eq!(from_morse("...---..."), None);
+/// ```
+pub fn from_morse(morse: &str) -> Option<String> {
+    let words = morse
+        .split('/')
+        .map(|word| {
+            word.split_whitespace()
+                .map(|code| {
+                    let i = MORSE.iter().position(|&m| m == code)?;
+                    Some(match i {
+                        0..=25 => char::from(b'A' + i as u8),
+                        _ => char::from(b'0' + (i - 26) as u8),
+                    })
+                })
+                .collect::<Option<String>>()
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(
+        words
+            .into_iter()
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("", DEFAULT_NORMALIZATION), "");
+        assert_eq!(
+            normalize("AN OKH: 12 KM.", DEFAULT_NORMALIZATION),
+            "AN OKHXX EINSZWO KMX"
+        );
+        assert_eq!(normalize("a.b!", &[('!', "EX"), ('.', "")]), "abEX");
+    }
+
+    #[test]
+    fn test_spell_digits() {
+        for digit in "0123456789".chars().map(String::from) {
+            assert_eq!(
+                spell_digits(&digit, &GERMAN_DIGITS),
+                normalize(&digit, DEFAULT_NORMALIZATION)
+            );
+        }
+
+        let text = "Ankunft 0800, Gruppe 3";
+        assert_eq!(
+            spell_digits(text, &GERMAN_DIGITS),
+            "Ankunft NULLACHTNULLNULL, Gruppe DREI"
+        );
+
+        let custom = ["O", "I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX"];
+        assert_eq!(spell_digits(text, &custom), "Ankunft OVIIIOO, Gruppe III");
+        assert_eq!(spell_digits("", &custom), "");
+    }
+
+    #[test]
+    fn test_digits_round_trip() {
+        let text = "UBOOT 1234567890 QUADRAT AB";
+        for codes in [&GERMAN_DIGITS, &ENGLISH_DIGITS] {
+            let policy = DigitPolicy::Replace(codes);
+            let replaced = replace_digits(text, policy);
+            assert!(!replaced.chars().any(|c| c.is_ascii_digit()));
+            assert_eq!(restore_digits(&replaced, policy), text);
+        }
+
+        let policy = DigitPolicy::Replace(&TOP_ROW_DIGITS);
+        assert_eq!(replace_digits("1941", policy), "QORQ");
+        assert_eq!(restore_digits("qorq", policy), "1941");
+        assert_eq!(restore_digits(text, DigitPolicy::Skip), text);
+
+        // Tables can be built at runtime, e.g. from a configuration file.
+        let words: Vec<String> = (0..10).map(|d| format!("D{}X", d)).collect();
+        let table: [&str; 10] = std::array::from_fn(|d| words[d].as_str());
+        let policy = DigitPolicy::Replace(&table);
+        assert_eq!(replace_digits("U 53", policy), "U D5XD3X");
+        assert_eq!(restore_digits("U D5XD3X", policy), "U 53");
+    }
+
+    #[test]
+    fn test_morse() {
+        assert_eq!(to_morse(""), "");
+        assert_eq!(to_morse("  az 09 .  "), ".- --.. / ----- ----.");
+        assert_eq!(from_morse(""), Some(String::new()));
+        assert_eq!(from_morse(" .-  --.. //----- "), Some("AZ 0".to_string()));
+        assert_eq!(from_morse(".-.-.-"), None);
+
+        let groups = "ABCDE FGHIJ KLMNO PQRST UVWXY Z0123 45678 9";
+        assert_eq!(from_morse(&to_morse(groups)).unwrap(), groups);
+    }
+}
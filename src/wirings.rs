@@ -0,0 +1,119 @@
+//! The historical rotor and reflector wirings as data.
+//!
+//! Each wiring lists the letters that the contacts A-Z are connected to. Notches are given as the
+//! letters showing in the window when the rotor turns over the rotor to its left, in alphabetical
+//! order. Rotors VI-VIII have two notches, at M and Z, so they turn over the next rotor twice per
+//! revolution, and the Enigma G rotors have many more. The named constructors on
+//! [`Rotor`](crate::Rotor) and [`Reflector`](crate::Reflector) are built from these constants.
+
+/// Wiring of rotor I.
+pub const ROTOR_I: &str = "EKMFLGDQVZNTOWYHXUSPAIBRCJ";
+/// Notches of rotor I.
+pub const ROTOR_I_NOTCHES: &str = "Q";
+/// Wiring of rotor II.
+pub const ROTOR_II: &str = "AJDKSIRUXBLHWTMCQGZNPYFVOE";
+/// Notches of rotor II.
+pub const ROTOR_II_NOTCHES: &str = "E";
+/// Wiring of rotor III.
+pub const ROTOR_III: &str = "BDFHJLCPRTXVZNYEIWGAKMUSQO";
+/// Notches of rotor III.
+pub const ROTOR_III_NOTCHES: &str = "V";
+/// Wiring of rotor IV.
+pub const ROTOR_IV: &str = "ESOVPZJAYQUIRHXLNFTGKDCMWB";
+/// Notches of rotor IV.
+pub const ROTOR_IV_NOTCHES: &str = "J";
+/// Wiring of rotor V.
+pub const ROTOR_V: &str = "VZBRGITYUPSDNHLXAWMJQOFECK";
+/// Notches of rotor V.
+pub const ROTOR_V_NOTCHES: &str = "Z";
+/// Wiring of rotor VI.
+pub const ROTOR_VI: &str = "JPGVOUMFYQBENHZRDKASXLICTW";
+/// Notches of rotor VI.
+pub const ROTOR_VI_NOTCHES: &str = "MZ";
+/// Wiring of rotor VII.
+pub const ROTOR_VII: &str = "NZJHGRCXMYSWBOUFAIVLPEKQDT";
+/// Notches of rotor VII.
+pub const ROTOR_VII_NOTCHES: &str = "MZ";
+/// Wiring of rotor VIII.
+pub const ROTOR_VIII: &str = "FKQHTLXOCBJSPDZRAMEWNIUYGV";
+/// Notches of rotor VIII.
+pub const ROTOR_VIII_NOTCHES: &str = "MZ";
+
+/// Wiring of Norway Enigma rotor I.
+pub const NORWAY_I: &str = "WTOKASUYVRBXJHQCPZEFMDINLG";
+/// Notches of Norway Enigma rotor I.
+pub const NORWAY_I_NOTCHES: &str = "Q";
+/// Wiring of Norway Enigma rotor II.
+pub const NORWAY_II: &str = "GJLPUBSWEMCTQVHXAOFZDRKYNI";
+/// Notches of Norway Enigma rotor II.
+pub const NORWAY_II_NOTCHES: &str = "E";
+/// Wiring of Norway Enigma rotor III.
+pub const NORWAY_III: &str = "JWFMHNBPUSDYTIXVZGRQLAOEKC";
+/// Notches of Norway Enigma rotor III.
+pub const NORWAY_III_NOTCHES: &str = "V";
+/// Wiring of Norway Enigma rotor IV.
+pub const NORWAY_IV: &str = "ESOVPZJAYQUIRHXLNFTGKDCMWB";
+/// Notches of Norway Enigma rotor IV.
+pub const NORWAY_IV_NOTCHES: &str = "J";
+/// Wiring of Norway Enigma rotor V.
+pub const NORWAY_V: &str = "HEJXQOTZBVFDASCILWPGYNMURK";
+/// Notches of Norway Enigma rotor V.
+pub const NORWAY_V_NOTCHES: &str = "Z";
+
+/// Wiring of the M4's Greek rotor beta, which sits left of the other rotors and never steps.
+pub const BETA: &str = "LEYJVCNIXWPBQMDRTAKZGFUHOS";
+/// Wiring of the M4's Greek rotor gamma, which sits left of the other rotors and never steps.
+pub const GAMMA: &str = "FSOKANUERHMBTIYCWLQPZXVGJD";
+
+/// Wiring of Enigma G-312 rotor I.
+pub const G_I: &str = "DMTWSILRUYQNKFEJCAZBPGXOHV";
+/// Notches of Enigma G-312 rotor I.
+pub const G_I_NOTCHES: &str = "ABCEFGIKLOPQSUVWZ";
+/// Wiring of Enigma G-312 rotor II.
+pub const G_II: &str = "HQZGPJTMOBLNCIFDYAWVEUSRKX";
+/// Notches of Enigma G-312 rotor II.
+pub const G_II_NOTCHES: &str = "ACDFGHKMNQSTVYZ";
+/// Wiring of Enigma G-312 rotor III.
+pub const G_III: &str = "UQNTLSZFMREHDPXKIBVYGJCWOA";
+/// Notches of Enigma G-312 rotor III.
+pub const G_III_NOTCHES: &str = "AEFHKMNRUWX";
+
+/// Wiring of railway Enigma rotor I, the Enigma K used by the Reichsbahn.
+pub const RAILWAY_I: &str = "JGDQOXUSCAMIFRVTPNEWKBLZYH";
+/// Notch of railway Enigma rotor I.
+pub const RAILWAY_I_NOTCHES: &str = "N";
+/// Wiring of railway Enigma rotor II.
+pub const RAILWAY_II: &str = "NTZPSFBOKMWRCJDIVLAEYUXHGQ";
+/// Notch of railway Enigma rotor II.
+pub const RAILWAY_II_NOTCHES: &str = "E";
+/// Wiring of railway Enigma rotor III.
+pub const RAILWAY_III: &str = "JVIUBHTCDYAKEQZPOSGXNRMWFL";
+/// Notch of railway Enigma rotor III.
+pub const RAILWAY_III_NOTCHES: &str = "Y";
+
+/// Wiring of reflector A.
+pub const REFLECTOR_A: &str = "EJMZALYXVBWFCRQUONTSPIKHGD";
+/// Wiring of reflector B.
+pub const REFLECTOR_B: &str = "YRUHQSLDPXNGOKMIEBFZCWVJAT";
+/// Wiring of reflector C.
+pub const REFLECTOR_C: &str = "FVPJIAOYEDRZXWGCTKUQSBNMHL";
+/// Wiring of the M4's thin reflector B, used with [`BETA`].
+pub const REFLECTOR_B_THIN: &str = "ENKQAUYWJICOPBLMDXZVFTHRGS";
+/// Wiring of the M4's thin reflector C, used with [`GAMMA`].
+pub const REFLECTOR_C_THIN: &str = "RDOBJNTKVEHMLFCWZAXGYIPSUQ";
+/// Wiring of the Norway Enigma reflector.
+pub const REFLECTOR_NORWAY: &str = "MOWJYPUXNDSRAIBFVLKZGQCHET";
+/// Wiring of the rewirable reflector D, UKW-D, as set up by [`Reflector::kd_default`]: the fixed
+/// pair `J-Y` and the pairs `AV BO CT DM EZ FN GX HQ IS KR LU PW`.
+///
+/// [`Reflector::kd_default`]: crate::Reflector::kd_default
+pub const REFLECTOR_D: &str = "VOTMZNXQSYRUDFBWHKICLAPGJE";
+/// Wiring of the Enigma G-312 reflector, which can be turned to any position.
+pub const REFLECTOR_G: &str = "RULQMZJSYGOCETKWDAHNBXPVIF";
+/// Wiring of the railway Enigma reflector, which can be set to any position but doesn't step.
+pub const REFLECTOR_RAILWAY: &str = "QYHOGNECVPUZTFDJAXWMKISRBL";
+
+/// Wiring of the entry wheel of the Enigma G and the railway Enigma, which connects the keys in
+/// keyboard order, i.e. the contacts A-Z to the keys Q, W, E, R, ... rather than A, B, C, D, ....
+/// The other machines' entry wheels connect each key to the contact of the same letter.
+pub const ENTRY_WHEEL_QWERTZ: &str = "QWERTZUIOASDFGHJKPYXCVBNML";
@@ -0,0 +1,33 @@
+/* Tests the C interface. Build and run from the repository root with:
+ *
+ *   cargo rustc --lib --features ffi --crate-type staticlib
+ *   cc tests/ffi.c -Iinclude target/debug/libenigma.a -lpthread -ldl -lm -o target/ffi_test
+ *   ./target/ffi_test
+ */
+#include <assert.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "enigma.h"
+
+int main(void) {
+    const char *rotors[] = {"I", "II", "III"};
+    size_t ring_settings[] = {1, 1, 1};
+    size_t positions[] = {0, 0, 0};
+
+    EnigmaMachine *machine = enigma_new(rotors, ring_settings, positions, "B", "");
+    assert(machine != NULL);
+    char *encrypted = enigma_encrypt(machine, "EXAMPLEXMESSAGE");
+    assert(encrypted != NULL);
+    assert(strcmp(encrypted, "AQTSWCKHEVCFYBS") == 0);
+    enigma_string_free(encrypted);
+    enigma_free(machine);
+
+    const char *bad_rotors[] = {"I", "IX", "III"};
+    machine = enigma_new(bad_rotors, ring_settings, positions, "B", "");
+    assert(machine == NULL);
+    assert(strcmp(enigma_last_error(), "unknown rotor name 'IX'") == 0);
+
+    puts("ok");
+    return 0;
+}
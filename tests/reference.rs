@@ -0,0 +1,104 @@
+//! Known-answer tests against published messages and the output of reference simulators, to check
+//! the wiring, ring settings and stepping together. Ring settings count from 0 here, so the ring
+//! setting listed as 01 on a key sheet is 0.
+use enigma::{EnigmaModel, MachineConfig};
+
+struct Vector {
+    name: &'static str,
+    config: fn() -> MachineConfig,
+    plaintext: &'static str,
+    ciphertext: &'static str,
+}
+
+const VECTORS: &[Vector] = &[
+    Vector {
+        // The textbook first check of a simulator.
+        name: "Enigma I, rings 01 01 01",
+        config: || {
+            MachineConfig::new(EnigmaModel::M3)
+                .rotors(&["I", "II", "III"])
+                .ring_settings(&[0, 0, 0])
+                .positions(&[0, 0, 0])
+                .reflector("B")
+        },
+        plaintext: "AAAAA",
+        ciphertext: "BDZGO",
+    },
+    Vector {
+        name: "Enigma I, rings 02 02 02",
+        config: || {
+            MachineConfig::new(EnigmaModel::M3)
+                .rotors(&["I", "II", "III"])
+                .ring_settings(&[1, 1, 1])
+                .positions(&[0, 0, 0])
+                .reflector("B")
+        },
+        plaintext: "AAAAA",
+        ciphertext: "EWTYX",
+    },
+    Vector {
+        // First part of the Operation Barbarossa message of 7 July 1941, with the full wartime
+        // complement of 10 plugboard pairs. Ring settings 02 21 12, message key BLA.
+        name: "Barbarossa",
+        config: || {
+            MachineConfig::new(EnigmaModel::M3)
+                .rotors(&["II", "IV", "V"])
+                .ring_settings(&[1, 20, 11])
+                .positions(&[1, 11, 0])
+                .reflector("B")
+                .plugboard("AV BS CG DL FU HZ IN KM OW RX")
+        },
+        plaintext: "AUFKLXABTEILUNGXVONXKURTINOWAXKURTINOWAXNORDWESTLXSEBEZXSEBEZXUAFFLIEGERSTRAS\
+                    ZERIQTUNGXDUBROWKIXDUBROWKIXOPOTSCHKAXOPOTSCHKAXUMXEINSAQTDREINULLXUHRANGETRE\
+                    TENXANGRIFFXINFXRGTX",
+        ciphertext: "EDPUDNRGYSZRCXNUYTPOMRMBOFKTBZREZKMLXLVEFGUEYSIOZVEQMIKUBPMMYLKLTTDEISMDICAGY\
+                     KUACTCDOMOHWXMUUIAUBSTSLRNBZSZWNRFXWFYSSXJZVIJHIDISHPRKLKAYUPADTXQSPINQMATLP\
+                     IFSVKDASCTACDPBOPVHJK",
+    },
+    Vector {
+        // U-534's M4 message of 25 November 1942, with Greek rotor Beta and ring setting 22 on
+        // the right rotor, message key VJNA.
+        name: "U-534",
+        config: || {
+            MachineConfig::new(EnigmaModel::M4)
+                .rotors(&["BETA", "II", "IV", "I"])
+                .ring_settings(&[0, 0, 0, 21])
+                .positions(&[21, 9, 13, 0])
+                .reflector("B-THIN")
+                .plugboard("AT BL DF GJ HM NW OP QY RZ VX")
+        },
+        plaintext: "VONVONJLOOKSJHFFTTTEINSEINSDREIZWOYYQNNSNEUNINHALTXXBEIANGRIFFUNTERWASSERGEDR\
+                    UECKTYWABOSXLETZTERGEGNERSTANDNULACHTDREINULUHRMARQUANTONJOTANEUNACHTSEYHSDRE\
+                    IYZWOZWONULGRADYACHTSMYSTOSSENACHXEKNSVIERMBFAELLTYNNNNNNOOOVIERYSICHTEINSNULL",
+        ciphertext: "NCZWVUSXPNYMINHZXMQXSFWXWLKJAHSHNMCOCCAKUQPMKCSMHKSEINJUSBLKIOSXCKUBHMLLXCSJ\
+                     USRRDVKOHULXWCCBGVLIYXEOAHXRHKKFVDREWEZLXOBAFGYUJQUKGRTVUKAMEURBVEKSUHHVOYHA\
+                     BCJWMAKLFKLMYFVNRIZRVVRTKOFDANJMOLBGFFLEOPRGTFLVRHOWOPBEKVWMUQFMPWPARMFHAGKX\
+                     IIBG",
+    },
+];
+
+#[test]
+fn encrypt_matches_reference() {
+    for vector in VECTORS {
+        let mut machine = (vector.config)().build().unwrap();
+        assert_eq!(
+            machine.encrypt(vector.plaintext),
+            vector.ciphertext,
+            "{}",
+            vector.name
+        );
+    }
+}
+
+#[test]
+fn decrypt_matches_reference() {
+    for vector in VECTORS {
+        let mut machine = (vector.config)().build().unwrap();
+        assert_eq!(
+            machine.encrypt(vector.ciphertext),
+            vector.plaintext,
+            "{}",
+            vector.name
+        );
+    }
+}
@@ -0,0 +1,61 @@
+//! Window readings around the double step, checked by hand against the stepping of the real
+//! machine. The rotors step before each letter is enciphered, so each reading is the one after
+//! that key press.
+use enigma::{ClockInt, Machine};
+
+/// The letters showing in the windows, left to right.
+fn window(machine: &Machine) -> String {
+    let (left, middle, right) = machine.positions();
+    [left, middle, right].iter().map(|p| p.to_char()).collect()
+}
+
+/// Presses a key for each of `expected`, checking the window after each.
+fn assert_windows(machine: &mut Machine, start: &str, expected: &[&str]) {
+    let mut chars = start.chars();
+    let mut next = || ClockInt::try_from(chars.next().unwrap()).unwrap();
+    machine.set_positions((next(), next(), next()));
+    assert_eq!(window(machine), start);
+
+    for (i, &expected) in expected.iter().enumerate() {
+        assert!(machine.encrypt_char('A').is_some());
+        assert_eq!(window(machine), expected, "after key press {}", i + 1);
+    }
+}
+
+#[test]
+fn double_step() {
+    // Rotor III turns over from V to W, stepping II onto its notch at E. On the next key press II
+    // steps again, taking I with it.
+    let mut machine = Machine::new(("I", "II", "III"), (0, 0, 0), (0, 0, 0), "B", "");
+    assert_windows(&mut machine, "ADU", &["ADV", "AEW", "BFX", "BFY"]);
+}
+
+#[test]
+fn double_step_reversed_order() {
+    // The same with rotor I on the right, turning over from Q to R.
+    let mut machine = Machine::new(("III", "II", "I"), (0, 0, 0), (0, 0, 0), "B", "");
+    assert_windows(&mut machine, "ADP", &["ADQ", "AER", "BFS", "BFT"]);
+}
+
+#[test]
+fn middle_rotor_starting_at_notch() {
+    // With II already at its notch, the first key press steps all three rotors.
+    let mut machine = Machine::new(("III", "II", "I"), (0, 0, 0), (0, 0, 0), "B", "");
+    assert_windows(&mut machine, "AEA", &["BFB", "BFC"]);
+}
+
+#[test]
+fn left_rotor_wraps_around() {
+    // Rotors I, II and III all turn over in one key press as the middle rotor double steps, and Z
+    // wraps round to A.
+    let mut machine = Machine::new(("I", "II", "III"), (0, 0, 0), (0, 0, 0), "B", "");
+    assert_windows(&mut machine, "ZEV", &["AFW", "AFX"]);
+}
+
+#[test]
+fn ring_settings_dont_move_notches() {
+    // The notches are on the alphabet ring, so the turnover happens at the same window letters
+    // whatever the ring settings.
+    let mut machine = Machine::new(("I", "II", "III"), (5, 12, 20), (0, 0, 0), "B", "");
+    assert_windows(&mut machine, "ADU", &["ADV", "AEW", "BFX", "BFY"]);
+}